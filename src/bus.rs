@@ -25,4 +25,10 @@ impl Bus {
     pub fn send<T: Into<Message>>(&mut self, msg: T) {
         self.messages.push_back(msg.into());
     }
+
+    // without any systems around, tests grab the messages themselves
+    #[cfg(test)]
+    pub fn take(&mut self) -> Vec<Message> {
+        self.messages.drain(..).collect()
+    }
 }
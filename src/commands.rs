@@ -1,15 +1,18 @@
 use crate::{
-    components::Centroidable,
-    entities::{Entities, Entity, EntityIndex},
+    components::{Centroidable, EffectKind, Polygon, WeaponKind},
+    constants::{BEAM_LENGTH, RAPID_FIRE_FACTOR, SPREAD_ANGLE, SPREAD_PROJECTILES, TEAM_NONE},
+    entities::{Eid, Entities, Entity, EntityIndex},
     forge::Forge,
-    quadtree::QuadTree,
+    math::intersection,
+    random::Random,
+    settings::{Physics, Settings},
     utils::generate_targeting_area,
 };
 
 use raylib::prelude::*;
 
 pub struct EntityCommands {
-    pub id: usize,
+    pub id: Eid,
     pub commands: Box<[Command]>,
 }
 
@@ -19,11 +22,19 @@ pub enum Command {
     Decelerate,
     RotateLeft,
     RotateRight,
-    Projectile,
+    FirePrimary,
     Boost,
-    Torpedo,
+    FireSecondary,
     Spawn(Spawn),
     TargetLock,
+    CyclePrimary,
+    CycleSecondary,
+    Countermeasure,
+    TargetPrevious,
+    TargetCrosshair,
+    TargetWeakest,
+    TargetAttacker,
+    Dampeners,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -36,24 +47,45 @@ enum Exhaust {
     Torpedo,
 }
 
+enum Slots {
+    Primary,
+    Secondary,
+}
+
+enum Selection {
+    Next,
+    Previous,
+    Crosshair,
+    Weakest,
+    Attacker,
+}
+
 impl Command {
     const ACCELERATE: u8 = 1;
     const DECELERATE: u8 = 2;
     const ROTATE_LEFT: u8 = 3;
     const ROTATE_RIGHT: u8 = 4;
-    const PROJECTILE: u8 = 5;
+    const FIRE_PRIMARY: u8 = 5;
     const BOOST: u8 = 6;
-    const TORPEDO: u8 = 7;
+    const FIRE_SECONDARY: u8 = 7;
     const SPAWN: u8 = 8;
     const TARGET_LOCK: u8 = 9;
+    const CYCLE_PRIMARY: u8 = 10;
+    const CYCLE_SECONDARY: u8 = 11;
+    const COUNTERMEASURE: u8 = 12;
+    const TARGET_PREVIOUS: u8 = 13;
+    const TARGET_CROSSHAIR: u8 = 14;
+    const TARGET_WEAKEST: u8 = 15;
+    const TARGET_ATTACKER: u8 = 16;
+    const DAMPENERS: u8 = 17;
 
     pub fn execute(
         &self,
         entities: &mut Entities,
-        eid: usize,
+        eid: Eid,
         forge: &Forge,
-        quadtree: &QuadTree,
-        h: &mut RaylibHandle,
+        settings: &Settings,
+        h: &mut dyn Random,
     ) {
         let Some(eidx) = entities.entity(eid) else {
             return;
@@ -61,14 +93,22 @@ impl Command {
 
         match self {
             Command::Accelerate => handle_accelerate(entities, eidx, forge, h),
-            Command::Decelerate => handle_decelerate(entities, eidx, forge, h),
+            Command::Decelerate => handle_decelerate(entities, eidx, settings.physics, forge, h),
             Command::RotateLeft => handle_rotate_left(entities, eidx, forge, h),
             Command::RotateRight => handle_rotate_right(entities, eidx, forge, h),
-            Command::Projectile => handle_projectile(entities, eidx, eid, forge),
+            Command::FirePrimary => handle_fire(entities, eidx, eid, Slots::Primary, forge),
             Command::Boost => handle_boost(entities, eidx),
-            Command::Torpedo => handle_torpedo(entities, eidx, eid, forge),
-            Command::Spawn(spawn) => handle_spawn(entities, forge, spawn),
-            Command::TargetLock => handle_target_lock(entities, eidx, quadtree),
+            Command::FireSecondary => handle_fire(entities, eidx, eid, Slots::Secondary, forge),
+            Command::Spawn(spawn) => handle_spawn(entities, forge, settings.physics, spawn),
+            Command::TargetLock => handle_target(entities, eidx, eid, Selection::Next),
+            Command::CyclePrimary => handle_cycle(entities, eidx, Slots::Primary),
+            Command::CycleSecondary => handle_cycle(entities, eidx, Slots::Secondary),
+            Command::Countermeasure => handle_countermeasure(entities, eidx, forge, h),
+            Command::TargetPrevious => handle_target(entities, eidx, eid, Selection::Previous),
+            Command::TargetCrosshair => handle_target(entities, eidx, eid, Selection::Crosshair),
+            Command::TargetWeakest => handle_target(entities, eidx, eid, Selection::Weakest),
+            Command::TargetAttacker => handle_target(entities, eidx, eid, Selection::Attacker),
+            Command::Dampeners => handle_dampeners(entities, eidx),
         }
     }
 
@@ -82,11 +122,19 @@ impl Command {
             Self::DECELERATE => Command::Decelerate,
             Self::ROTATE_LEFT => Command::RotateLeft,
             Self::ROTATE_RIGHT => Command::RotateRight,
-            Self::PROJECTILE => Command::Projectile,
+            Self::FIRE_PRIMARY => Command::FirePrimary,
             Self::BOOST => Command::Boost,
-            Self::TORPEDO => Command::Torpedo,
+            Self::FIRE_SECONDARY => Command::FireSecondary,
             Self::SPAWN => Command::Spawn(Spawn::from_bytes(data)),
             Self::TARGET_LOCK => Command::TargetLock,
+            Self::CYCLE_PRIMARY => Command::CyclePrimary,
+            Self::CYCLE_SECONDARY => Command::CycleSecondary,
+            Self::COUNTERMEASURE => Command::Countermeasure,
+            Self::TARGET_PREVIOUS => Command::TargetPrevious,
+            Self::TARGET_CROSSHAIR => Command::TargetCrosshair,
+            Self::TARGET_WEAKEST => Command::TargetWeakest,
+            Self::TARGET_ATTACKER => Command::TargetAttacker,
+            Self::DAMPENERS => Command::Dampeners,
             _ => panic!("wtf ctype {}", ctype),
         }
     }
@@ -101,14 +149,22 @@ impl Command {
             Command::Decelerate => bytes.push(Self::DECELERATE),
             Command::RotateLeft => bytes.push(Self::ROTATE_LEFT),
             Command::RotateRight => bytes.push(Self::ROTATE_RIGHT),
-            Command::Projectile => bytes.push(Self::PROJECTILE),
+            Command::FirePrimary => bytes.push(Self::FIRE_PRIMARY),
             Command::Boost => bytes.push(Self::BOOST),
-            Command::Torpedo => bytes.push(Self::TORPEDO),
+            Command::FireSecondary => bytes.push(Self::FIRE_SECONDARY),
             Command::Spawn(spawn) => {
                 bytes.push(Self::SPAWN);
                 bytes.extend_from_slice(&spawn.to_bytes().into_vec());
             }
             Command::TargetLock => bytes.push(Self::TARGET_LOCK),
+            Command::CyclePrimary => bytes.push(Self::CYCLE_PRIMARY),
+            Command::CycleSecondary => bytes.push(Self::CYCLE_SECONDARY),
+            Command::Countermeasure => bytes.push(Self::COUNTERMEASURE),
+            Command::TargetPrevious => bytes.push(Self::TARGET_PREVIOUS),
+            Command::TargetCrosshair => bytes.push(Self::TARGET_CROSSHAIR),
+            Command::TargetWeakest => bytes.push(Self::TARGET_WEAKEST),
+            Command::TargetAttacker => bytes.push(Self::TARGET_ATTACKER),
+            Command::Dampeners => bytes.push(Self::DAMPENERS),
         }
 
         bytes.into_boxed_slice()
@@ -168,9 +224,14 @@ impl Spawn {
     }
 }
 
-fn handle_spawn(entities: &mut Entities, forge: &Forge, spawn: &Spawn) {
+fn handle_spawn(entities: &mut Entities, forge: &Forge, physics: Physics, spawn: &Spawn) {
     let entity = match spawn {
-        Spawn::Triship(x, y) => Entity::Triship(forge.triship(Vector2::new(*x as f32, *y as f32))),
+        Spawn::Triship(x, y) => Entity::Triship(forge.triship(
+            Vector2::new(*x as f32, *y as f32),
+            TEAM_NONE,
+            0,
+            physics,
+        )),
     };
 
     entities.add(entity);
@@ -180,7 +241,7 @@ fn handle_accelerate(
     entities: &mut Entities,
     eidx: EntityIndex,
     forge: &Forge,
-    h: &mut RaylibHandle,
+    h: &mut dyn Random,
 ) {
     let (rotation, motion) = match eidx {
         EntityIndex::Triship(idx) => {
@@ -195,7 +256,7 @@ fn handle_accelerate(
             let e = &mut entities.projectiles[idx].entity;
             (e.body.state.new.rotation, &mut e.motion)
         }
-        _ => panic!("wtf accelerate {:?}", eidx),
+        _ => return,
     };
 
     motion.velocity += rotation * motion.acceleration;
@@ -247,8 +308,9 @@ fn handle_accelerate(
 fn handle_decelerate(
     entities: &mut Entities,
     eidx: EntityIndex,
+    physics: Physics,
     forge: &Forge,
-    h: &mut RaylibHandle,
+    h: &mut dyn Random,
 ) {
     let (rotation, motion) = match eidx {
         EntityIndex::Triship(idx) => {
@@ -259,10 +321,27 @@ fn handle_decelerate(
             let e = &mut entities.torpedoes[idx].entity;
             (e.body.state.new.rotation, &mut e.motion)
         }
-        _ => panic!("wtf decelerate {:?}", eidx),
+        _ => return,
     };
 
-    motion.velocity -= rotation * (motion.acceleration / 4.0);
+    // in newtonian space we retro-burn against the drift instead of reversing,
+    // the thrusters then have to fire along the direction we're drifting in
+    let rotation = match physics {
+        Physics::Arcade => {
+            motion.velocity -= rotation * (motion.acceleration / 4.0);
+            rotation
+        }
+        Physics::Newtonian => {
+            let speed = motion.velocity.length();
+            if speed == 0.0 {
+                return;
+            }
+
+            let direction = motion.velocity / speed;
+            motion.velocity -= direction * motion.acceleration.min(speed);
+            direction
+        }
+    };
 
     // spawn exhaust particles if it's a triship
     if let EntityIndex::Triship(idx) = eidx {
@@ -290,11 +369,18 @@ fn handle_decelerate(
     }
 }
 
+fn handle_dampeners(entities: &mut Entities, eidx: EntityIndex) {
+    if let EntityIndex::Triship(idx) = eidx {
+        let e = &mut entities.triships[idx].entity;
+        e.dampeners = !e.dampeners;
+    }
+}
+
 fn handle_rotate_left(
     entities: &mut Entities,
     eidx: EntityIndex,
     forge: &Forge,
-    h: &mut RaylibHandle,
+    h: &mut dyn Random,
 ) {
     let (motion, old_rotation) = match eidx {
         EntityIndex::Triship(idx) => {
@@ -305,7 +391,7 @@ fn handle_rotate_left(
             let e = &mut entities.torpedoes[idx].entity;
             (&mut e.motion, e.body.state.old.rotation)
         }
-        _ => panic!("wtf rotate left {:?}", eidx),
+        _ => return,
     };
 
     motion.rotation_speed -= motion.rotation_acceleration;
@@ -338,7 +424,7 @@ fn handle_rotate_right(
     entities: &mut Entities,
     eidx: EntityIndex,
     forge: &Forge,
-    h: &mut RaylibHandle,
+    h: &mut dyn Random,
 ) {
     let (motion, old_rotation) = match eidx {
         EntityIndex::Triship(idx) => {
@@ -349,7 +435,7 @@ fn handle_rotate_right(
             let e = &mut entities.torpedoes[idx].entity;
             (&mut e.motion, e.body.state.old.rotation)
         }
-        _ => panic!("wtf rotate right {:?}", eidx),
+        _ => return,
     };
 
     motion.rotation_speed += motion.rotation_acceleration;
@@ -378,29 +464,13 @@ fn handle_rotate_right(
     }
 }
 
-fn handle_projectile(entities: &mut Entities, eidx: EntityIndex, id: usize, forge: &Forge) {
-    let (body, velocity) = match eidx {
-        EntityIndex::Triship(idx) => {
-            let e = &entities.triships[idx].entity;
-            (&e.body, e.motion.velocity)
-        }
-        _ => panic!("wtf projectile {:?}", eidx),
-    };
-
-    let rotation = body.state.new.rotation;
-    let position = body.polygon.vertexes.new[1];
-    let projectile = forge.projectile(position, rotation, velocity, id);
-
-    entities.add(Entity::Projectile(projectile));
-}
-
 fn handle_boost(entities: &mut Entities, eidx: EntityIndex) {
     let (motion, boost) = match eidx {
         EntityIndex::Triship(idx) => {
             let e = &mut entities.triships[idx].entity;
             (&mut e.motion, &mut e.boost)
         }
-        _ => panic!("wtf boost {:?}", eidx),
+        _ => return,
     };
 
     if boost.active {
@@ -417,25 +487,125 @@ fn handle_boost(entities: &mut Entities, eidx: EntityIndex) {
     motion.acceleration = boost.acceleration;
 }
 
-fn handle_torpedo(entities: &mut Entities, eidx: EntityIndex, id: usize, forge: &Forge) {
-    let (body, velocity, cooldown, target) = match eidx {
+fn handle_fire(entities: &mut Entities, eidx: EntityIndex, id: Eid, slot: Slots, forge: &Forge) {
+    let (slot, rapid_fire) = match eidx {
         EntityIndex::Triship(idx) => {
             let e = &mut entities.triships[idx].entity;
-            (
-                &e.body,
-                e.motion.velocity,
-                &mut e.cooldown_torpedo,
-                &e.targeting,
-            )
+            let rapid_fire = e
+                .effects
+                .iter()
+                .any(|x| matches!(x.kind, EffectKind::RapidFire));
+
+            let slot = match slot {
+                Slots::Primary => &mut e.primary,
+                Slots::Secondary => &mut e.secondary,
+            };
+
+            (slot, rapid_fire)
         }
-        _ => panic!("wtf torpedo {:?}", eidx),
+        _ => return,
+    };
+
+    let Some(weapon) = slot.weapon() else {
+        return;
     };
 
-    if cooldown.current != 0 {
+    if !weapon.ready() {
         return;
     }
 
-    cooldown.current = cooldown.max;
+    weapon.cooldown.current = if rapid_fire {
+        weapon.cooldown.max / RAPID_FIRE_FACTOR
+    } else {
+        weapon.cooldown.max
+    };
+
+    if let Some(ammo) = &mut weapon.ammo {
+        ammo.current -= 1;
+    }
+
+    let heat = &mut weapon.heat;
+    heat.current = heat.current.saturating_add(heat.shot).min(heat.max);
+    if heat.current == heat.max {
+        heat.overheated = true;
+    }
+
+    let kind = weapon.kind;
+    let damage = weapon.damage;
+
+    match kind {
+        WeaponKind::Cannon => handle_cannon(entities, eidx, id, damage, forge),
+        WeaponKind::Spread => handle_spread(entities, eidx, id, damage, forge),
+        WeaponKind::Torpedo => handle_torpedo(entities, eidx, id, damage, forge),
+        WeaponKind::Mine => handle_mine(entities, eidx, id, damage, forge),
+        WeaponKind::Beam => handle_beam(entities, eidx, id, damage, forge),
+    }
+}
+
+fn handle_cycle(entities: &mut Entities, eidx: EntityIndex, slot: Slots) {
+    let e = match eidx {
+        EntityIndex::Triship(idx) => &mut entities.triships[idx].entity,
+        _ => return,
+    };
+
+    match slot {
+        Slots::Primary => e.primary.cycle(),
+        Slots::Secondary => e.secondary.cycle(),
+    }
+}
+
+fn handle_cannon(entities: &mut Entities, eidx: EntityIndex, id: Eid, damage: f32, forge: &Forge) {
+    let (body, velocity) = match eidx {
+        EntityIndex::Triship(idx) => {
+            let e = &entities.triships[idx].entity;
+            (&e.body, e.motion.velocity)
+        }
+        _ => return,
+    };
+
+    let rotation = body.state.new.rotation;
+    let position = body.polygon.vertexes.new[1];
+    let projectile = forge.projectile(position, rotation, velocity, id, damage);
+
+    entities.add(Entity::Projectile(projectile));
+}
+
+fn handle_spread(entities: &mut Entities, eidx: EntityIndex, id: Eid, damage: f32, forge: &Forge) {
+    let (body, velocity) = match eidx {
+        EntityIndex::Triship(idx) => {
+            let e = &entities.triships[idx].entity;
+            (&e.body, e.motion.velocity)
+        }
+        _ => return,
+    };
+
+    let rotation = body.state.new.rotation;
+    let angle = rotation.y.atan2(rotation.x);
+    let position = body.polygon.vertexes.new[1];
+
+    // fan the projectiles out evenly around the nose
+    let projectiles = (0..SPREAD_PROJECTILES)
+        .map(|i| {
+            let offset = i as f32 - (SPREAD_PROJECTILES - 1) as f32 / 2.0;
+            let (sin, cos) = (angle + offset * SPREAD_ANGLE).sin_cos();
+
+            forge.projectile(position, Vector2::new(cos, sin), velocity, id, damage)
+        })
+        .collect::<Vec<_>>();
+
+    for projectile in projectiles {
+        entities.add(Entity::Projectile(projectile));
+    }
+}
+
+fn handle_torpedo(entities: &mut Entities, eidx: EntityIndex, id: Eid, damage: f32, forge: &Forge) {
+    let (body, velocity, target) = match eidx {
+        EntityIndex::Triship(idx) => {
+            let e = &entities.triships[idx].entity;
+            (&e.body, e.motion.velocity, &e.targeting)
+        }
+        _ => return,
+    };
 
     // only use target if it's been locked
     let target = if target.timer.current == 0 {
@@ -451,89 +621,246 @@ fn handle_torpedo(entities: &mut Entities, eidx: EntityIndex, id: usize, forge:
         vertexes[0].y * 0.4 + vertexes[1].y * 0.6,
     );
 
-    let torpedo = forge.torpedo(position, rotation, velocity, id, target);
+    let torpedo = forge.torpedo(position, rotation, velocity, id, target, damage);
 
     entities.add(Entity::Torpedo(torpedo));
 }
 
-fn handle_target_lock(entities: &mut Entities, eidx: EntityIndex, quadtree: &QuadTree) {
-    let (centroid, eid_target) = match eidx {
+fn handle_mine(entities: &mut Entities, eidx: EntityIndex, id: Eid, damage: f32, forge: &Forge) {
+    let body = match eidx {
+        EntityIndex::Triship(idx) => &entities.triships[idx].entity.body,
+        _ => return,
+    };
+
+    // drop it from the stern
+    let vertexes = &body.polygon.vertexes.new;
+    let position = Vector2::new(
+        (vertexes[0].x + vertexes[2].x) / 2.0,
+        (vertexes[0].y + vertexes[2].y) / 2.0,
+    );
+
+    let mine = forge.mine(position, body.state.new.rotation, id, damage);
+
+    entities.add(Entity::Mine(mine));
+}
+
+fn handle_beam(entities: &mut Entities, eidx: EntityIndex, id: Eid, damage: f32, forge: &Forge) {
+    let body = match eidx {
+        EntityIndex::Triship(idx) => &entities.triships[idx].entity.body,
+        _ => return,
+    };
+
+    let origin = body.polygon.vertexes.new[1];
+    let mut end = origin + body.state.new.rotation * BEAM_LENGTH;
+
+    let area = Rectangle {
+        x: origin.x.min(end.x),
+        y: origin.y.min(end.y),
+        width: (origin.x - end.x).abs(),
+        height: (origin.y - end.y).abs(),
+    };
+
+    // the closest edge crossing the beam is what we hit
+    let mut target = None;
+    let mut dist_min = f32::MAX;
+
+    // flags can't be shot
+    let candidates = candidates(entities, id)
+        .filter(|(_, eidx, _)| !matches!(eidx, EntityIndex::Flag(_)))
+        .filter(|(_, _, polygon)| polygon.bounds_real.new.check_collision_recs(&area))
+        .map(|(eid, _, polygon)| (eid, polygon));
+
+    for (eid, polygon) in candidates {
+        let vertexes = &polygon.vertexes.new;
+
+        for i in 0..vertexes.len() {
+            let a = vertexes[i];
+            let b = vertexes[(i + 1) % vertexes.len()];
+
+            let Some(p) = intersection(origin, end, a, b) else {
+                continue;
+            };
+
+            let dist = (p - origin).length_sqr();
+            if dist < dist_min {
+                dist_min = dist;
+                target = Some(eid);
+                end = p;
+            }
+        }
+    }
+
+    let beam = forge.beam(origin, end, target, id, damage);
+
+    entities.add(Entity::Beam(beam));
+}
+
+fn handle_countermeasure(
+    entities: &mut Entities,
+    eidx: EntityIndex,
+    forge: &Forge,
+    h: &mut dyn Random,
+) {
+    let e = match eidx {
+        EntityIndex::Triship(idx) => &mut entities.triships[idx].entity,
+        _ => return,
+    };
+
+    let c = &mut e.countermeasures;
+
+    if c.cooldown.current != 0 || c.ammo.current == 0 {
+        return;
+    }
+
+    c.cooldown.current = c.cooldown.max;
+    c.ammo.current -= 1;
+
+    let vertexes = &e.body.polygon.vertexes.new;
+    let position = Vector2::new(
+        (vertexes[0].x + vertexes[2].x) / 2.0,
+        (vertexes[0].y + vertexes[2].y) / 2.0,
+    );
+
+    for flare in forge.flares(position, e.body.state.new.rotation, e.motion.velocity, h) {
+        entities.add(Entity::Flare(flare));
+    }
+}
+
+fn handle_target(entities: &mut Entities, eidx: EntityIndex, id: Eid, selection: Selection) {
+    let (centroid, rotation, eid_target, eid_attacker) = match eidx {
         EntityIndex::Triship(idx) => {
-            let e = &mut entities.triships[idx].entity;
-            (e.body.state.new.shape.centroid(), e.targeting.eid)
+            let e = &entities.triships[idx].entity;
+            (
+                e.body.state.new.shape.centroid(),
+                e.body.state.new.rotation,
+                e.targeting.eid,
+                e.targeting.attacker,
+            )
         }
-        _ => panic!("wtf target lock {:?}", eidx),
+        _ => return,
     };
 
     let area = generate_targeting_area(centroid);
 
-    let mut targets = quadtree
-        .get(&area, entities)
-        .iter()
-        .filter_map(|x| {
-            // don't target self
-            if *x == eidx {
-                return None;
-            }
-
-            let c = match *x {
-                EntityIndex::Triship(idx) => entities.triships[idx]
-                    .entity
-                    .body
-                    .state
-                    .new
-                    .shape
-                    .centroid(),
+    // our own torpedoes aren't worth a lock, objectives have no life
+    let mut targets = candidates(entities, id)
+        .filter_map(|(eid, eidx, polygon)| {
+            let life = match eidx {
+                EntityIndex::Triship(idx) => Some(entities.triships[idx].entity.life),
+                EntityIndex::Torpedo(idx) => {
+                    let e = &entities.torpedoes[idx].entity;
+
+                    if e.owner_id == id {
+                        return None;
+                    }
+
+                    Some(e.life)
+                }
+                EntityIndex::Flag(_) => None,
                 _ => return None,
             };
 
-            let cx = c.x - centroid.x;
-            let cy = c.y - centroid.y;
-
-            let dist_sqr = (cx * cx) + (cy * cy);
-
-            Some((*x, dist_sqr))
+            Some((eid, polygon, life))
         })
+        .filter(|(_, polygon, _)| area.check_collision_recs(&polygon.bounds_meld.new))
+        .map(|(eid, polygon, life)| (eid, polygon.bounds_real.new.centroid(), life))
         .collect::<Vec<_>>();
 
-    targets.sort_unstable_by(|(_, dist_a), (_, dist_b)| dist_a.total_cmp(dist_b));
+    match selection {
+        Selection::Next | Selection::Previous | Selection::Attacker => {
+            targets.sort_unstable_by(|(_, a, _), (_, b, _)| {
+                (*a - centroid)
+                    .length_sqr()
+                    .total_cmp(&(*b - centroid).length_sqr())
+            });
+        }
+        Selection::Crosshair => {
+            // the smaller the angle to where we're pointing, the higher the dot product
+            targets.sort_unstable_by(|(_, a, _), (_, b, _)| {
+                let dot_a = (*a - centroid).normalized().dot(rotation);
+                let dot_b = (*b - centroid).normalized().dot(rotation);
+                dot_b.total_cmp(&dot_a)
+            });
+        }
+        Selection::Weakest => {
+            targets.sort_unstable_by(|(_, _, a), (_, _, b)| {
+                a.unwrap_or(f32::MAX).total_cmp(&b.unwrap_or(f32::MAX))
+            });
+        }
+    }
 
     let idx_current = match eid_target {
-        Some(eid) => targets.iter().position(|(eidx, _)| match eidx {
-            EntityIndex::Triship(idx) => entities.triships[*idx].id == eid,
-            _ => false,
-        }),
+        Some(eid) => targets.iter().position(|(x, _, _)| *x == eid),
         None => None,
     };
 
-    let eidx_target = match idx_current {
-        Some(idx) if idx + 1 < targets.len() => {
-            let (eidx, _) = targets[idx + 1];
-            Some(eidx)
-        }
-        _ => {
-            if targets.len() == 0 || idx_current.is_some() {
-                None
-            } else {
-                let (eidx, _) = targets[0];
-                Some(eidx)
+    // cycle through the targets, going past either end unlocks
+    let idx_target = match selection {
+        Selection::Next | Selection::Crosshair | Selection::Weakest => match idx_current {
+            Some(idx) if idx + 1 < targets.len() => Some(idx + 1),
+            Some(_) => None,
+            None if targets.is_empty() => None,
+            None => Some(0),
+        },
+        Selection::Previous => match idx_current {
+            Some(idx) if idx > 0 => Some(idx - 1),
+            Some(_) => None,
+            None => targets.len().checked_sub(1),
+        },
+        Selection::Attacker => {
+            match targets
+                .iter()
+                .position(|(x, _, _)| Some(*x) == eid_attacker)
+            {
+                Some(idx) => Some(idx),
+                // attacker is gone or out of reach, keep what we have
+                None => return,
             }
         }
     };
 
-    let eid_target = match eidx_target {
-        Some(eidx) => match eidx {
-            EntityIndex::Triship(idx) => Some(entities.triships[idx].id),
-            _ => None,
-        },
-        None => None,
-    };
-
     let targeting = match eidx {
         EntityIndex::Triship(idx) => &mut entities.triships[idx].entity.targeting,
-        _ => panic!("wtf targeting {:?}", eidx),
+        _ => return,
     };
 
-    targeting.eid = eid_target;
+    targeting.eid = idx_target.map(|idx| targets[idx].0);
     targeting.timer.current = targeting.timer.max;
 }
+
+// the quadtree is from last tick and its indexes might be stale by now, so whatever
+// looks for something to hit or target goes through everything that could be, but us
+fn candidates(entities: &Entities, id: Eid) -> impl Iterator<Item = (Eid, EntityIndex, &Polygon)> {
+    let triships = entities
+        .triships
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (x.id, EntityIndex::Triship(i), &x.entity.body.polygon));
+    let torpedoes = entities
+        .torpedoes
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (x.id, EntityIndex::Torpedo(i), &x.entity.body.polygon));
+    let asteroids = entities
+        .asteroids
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (x.id, EntityIndex::Asteroid(i), &x.entity.body.polygon));
+    let mines = entities
+        .mines
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (x.id, EntityIndex::Mine(i), &x.entity.body.polygon));
+    let flags = entities
+        .flags
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (x.id, EntityIndex::Flag(i), &x.entity.body.polygon));
+
+    triships
+        .chain(torpedoes)
+        .chain(asteroids)
+        .chain(mines)
+        .chain(flags)
+        .filter(move |(eid, _, _)| *eid != id)
+}
@@ -1,6 +1,9 @@
 use std::f32::consts::FRAC_PI_4;
 
 use raylib::prelude::*;
+use serde::Deserialize;
+
+use crate::{constants::ASTEROID_VERTEXES, entities::Eid};
 
 pub mod traits;
 
@@ -13,6 +16,14 @@ pub struct Triangle {
     pub v3: Vector2,
 }
 
+// convex-ish polygon with the vertexes evenly spread around the center,
+// each vertex has its own distance from the center
+#[derive(Clone, Copy)]
+pub struct Rock {
+    pub center: Vector2,
+    pub radii: [f32; ASTEROID_VERTEXES],
+}
+
 pub struct Body<T> {
     pub state: Generation<RotatedShape<T>>,
     pub color: Color,
@@ -40,6 +51,16 @@ pub struct Motion {
     pub rotation_speed_max: f32,
 }
 
+// what slows it down when nothing is pushing it
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Drag {
+    #[default]
+    Never,
+    Always,
+    // only with arcade physics
+    Arcade,
+}
+
 pub struct Boost {
     pub acceleration: f32,
     pub speed_max: f32,
@@ -50,11 +71,105 @@ pub struct Boost {
     pub active: bool,
 }
 
+// absorbs damage before the hull does, starts regenerating once it hasn't been hit for a while
+pub struct Shield {
+    pub current: f32,
+    pub max: f32,
+    pub regen: f32,
+    pub delay: Load,
+    pub flash: Load,
+    // hull edge that took the last hit and whether it got through the shield
+    pub edge: usize,
+    pub breached: bool,
+}
+
 pub struct Load {
     pub current: u8,
     pub max: u8,
 }
 
+pub struct Effect {
+    pub kind: EffectKind,
+    pub lifetime: Load,
+}
+
+pub enum EffectKind {
+    RapidFire,
+}
+
+pub struct Countermeasures {
+    pub ammo: Load,
+    pub cooldown: Load,
+}
+
+pub struct Slot {
+    pub weapons: Vec<Weapon>,
+    pub selected: usize,
+}
+
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub damage: f32,
+    pub cooldown: Load,
+    // unlimited if none
+    pub ammo: Option<Load>,
+    pub heat: Heat,
+}
+
+// every shot heats the weapon up, once it hits the max it's jammed until fully cooled down
+pub struct Heat {
+    pub current: u8,
+    pub max: u8,
+    pub shot: u8,
+    pub overheated: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum WeaponKind {
+    Cannon,
+    Spread,
+    Torpedo,
+    Mine,
+    Beam,
+}
+
+impl Slot {
+    pub fn weapon(&mut self) -> Option<&mut Weapon> {
+        self.weapons.get_mut(self.selected)
+    }
+
+    pub fn cycle(&mut self) {
+        if self.weapons.is_empty() {
+            return;
+        }
+
+        self.selected = (self.selected + 1) % self.weapons.len();
+    }
+}
+
+impl Weapon {
+    pub fn ready(&self) -> bool {
+        let ammo = match &self.ammo {
+            Some(ammo) => ammo.current != 0,
+            None => true,
+        };
+
+        self.cooldown.current == 0 && !self.heat.overheated && ammo
+    }
+}
+
+impl WeaponKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            WeaponKind::Cannon => "CANNON",
+            WeaponKind::Spread => "SPREAD",
+            WeaponKind::Torpedo => "TORPEDO",
+            WeaponKind::Mine => "MINE",
+            WeaponKind::Beam => "BEAM",
+        }
+    }
+}
+
 pub struct Polygon {
     pub dirty: bool,
     pub vertexes: Generation<Vec<Vector2>>,
@@ -62,8 +177,54 @@ pub struct Polygon {
     pub bounds_meld: Generation<Rectangle>,
 }
 
+// what happens when a collider runs into something, sensors and killers notice the overlap
+// but never get pushed around, everything else is moved back to where they first touched
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Response {
+    Sensor,
+    // destroys whatever touches it
+    Kill,
+    // stops where it touched, it's going off anyway
+    Stop,
+    // gives way along the contact, halfway if the other one does too
+    Push,
+    // never moves, whatever runs into it bounces off
+    Bounce,
+}
+
+// the layer a collider sits on and the layers it wants to hit, a pair only collides
+// if both of them want to
+#[derive(Clone, Copy)]
+pub struct Layers {
+    pub layer: u16,
+    pub mask: u16,
+    pub response: Response,
+}
+
+impl Layers {
+    pub const NONE: Layers = Layers {
+        layer: 0,
+        mask: 0,
+        response: Response::Sensor,
+    };
+
+    pub fn new(layer: u16, mask: u16, response: Response) -> Self {
+        Self {
+            layer,
+            mask,
+            response,
+        }
+    }
+
+    pub fn hits(&self, other: &Layers) -> bool {
+        self.mask & other.layer != 0 && other.mask & self.layer != 0
+    }
+}
+
 pub struct Targeting {
-    pub eid: Option<usize>,
+    pub eid: Option<Eid>,
+    // whoever damaged us last, so we can target them back
+    pub attacker: Option<Eid>,
     pub timer: Load,
     pub visual: Load,
 }
@@ -1,8 +1,10 @@
+use std::f32::consts::TAU;
+
 use super::*;
 
 use crate::math::*;
 
-pub trait Shape: Renewable + Acceleratable + Rotatable {}
+pub trait Shape: Renewable + Acceleratable + Rotatable + Generationable {}
 
 pub trait Vertexable {
     fn vertexes(&self, rotation: Vector2) -> Vec<Vector2>;
@@ -43,6 +45,7 @@ pub trait Cullable {
 impl Shape for Body<Triangle> {}
 impl Shape for Body<Rectangle> {}
 impl Shape for Body<Vector2> {}
+impl Shape for Body<Rock> {}
 
 impl Cullable for Rectangle {
     fn cull(&self, viewport: Rectangle) -> bool {
@@ -68,6 +71,12 @@ impl Centroidable for Rectangle {
     }
 }
 
+impl Centroidable for Rock {
+    fn centroid(&self) -> Vector2 {
+        self.center
+    }
+}
+
 impl Lerpable<Triangle> for Generation<RotatedShape<Triangle>> {
     fn lerp(&self, amount: f32) -> Triangle {
         Triangle {
@@ -111,6 +120,15 @@ impl Lerpable<Rectangle> for Generation<Rectangle> {
     }
 }
 
+impl Lerpable<Rock> for Generation<RotatedShape<Rock>> {
+    fn lerp(&self, amount: f32) -> Rock {
+        Rock {
+            center: self.old.shape.center.lerp(self.new.shape.center, amount),
+            radii: self.new.shape.radii,
+        }
+    }
+}
+
 impl Lerpable<Vector2> for Generation<RotatedShape<Vector2>> {
     fn lerp(&self, amount: f32) -> Vector2 {
         self.old.shape.lerp(self.new.shape, amount)
@@ -145,6 +163,12 @@ impl Acceleratable for Rectangle {
     }
 }
 
+impl Acceleratable for Rock {
+    fn accelerate(&mut self, by: Vector2) {
+        self.center += by;
+    }
+}
+
 impl Acceleratable for Vector2 {
     fn accelerate(&mut self, by: Vector2) {
         self.x += by.x;
@@ -202,6 +226,22 @@ impl Vertexable for Vector2 {
     }
 }
 
+impl Vertexable for Rock {
+    fn vertexes(&self, rotation: Vector2) -> Vec<Vector2> {
+        let rad = rotation.y.atan2(rotation.x);
+        let step = TAU / self.radii.len() as f32;
+
+        self.radii
+            .iter()
+            .enumerate()
+            .map(|(i, radius)| {
+                let (sin, cos) = (rad + step * i as f32).sin_cos();
+                self.center + Vector2::new(cos, sin) * *radius
+            })
+            .collect()
+    }
+}
+
 impl Boundable for Vec<Vector2> {
     fn bounds(&self) -> Rectangle {
         let mut min_x = 0.0;
@@ -1,4 +1,4 @@
-use raylib::color::Color;
+use raylib::{color::Color, prelude::Vector2};
 
 pub const WINDOW_WIDTH: i32 = 1280;
 pub const WINDOW_HEIGHT: i32 = 720;
@@ -8,8 +8,6 @@ pub const VIEWPORT_WIDTH: i32 = 1150;
 pub const VIEWPORT_HEIGHT: i32 = 720;
 pub const TARGETING_AREA_WIDTH: i32 = VIEWPORT_WIDTH - 200;
 pub const TARGETING_AREA_HEIGHT: i32 = VIEWPORT_HEIGHT - 200;
-pub const COSMOS_WIDTH: i32 = 20000;
-pub const COSMOS_HEIGHT: i32 = 20000;
 pub const TICK_SCHEDULED: u32 = 4;
 pub const MINIMAP_X: i32 = HUD_X;
 pub const MINIMAP_Y: i32 = HUD_HEIGHT - MINIMAP_HEIGHT;
@@ -27,3 +25,80 @@ pub const DEBUG_COLOR: Color = Color::WHITESMOKE;
 pub const RESPAWN_TIMER: u8 = 50;
 pub const STARFIELD_WIDTH: i32 = 512;
 pub const STARFIELD_HEIGHT: i32 = 512;
+pub const TEAM_NONE: u8 = u8::MAX;
+pub const TEAM_COLORS: [Color; 2] = [Color::SKYBLUE, Color::GOLD];
+pub const FLAG_CARRIER_SPEED_FACTOR: f32 = 0.7;
+pub const ASTEROID_VERTEXES: usize = 8;
+pub const ASTEROID_RADIUS_MIN: f32 = 30.0;
+pub const ASTEROID_RADIUS_MAX: f32 = 240.0;
+pub const ASTEROID_BOUNCE: f32 = 4.0;
+// sine of the steepest angle a projectile still glances off at
+pub const RICOCHET_IMPACT: f32 = 0.3;
+pub const COLLISION_STEPS_MAX: usize = 64;
+pub const BOUNDARY_ZONE: f32 = 1000.0;
+pub const BOUNDARY_DAMAGE: f32 = 0.5;
+pub const BOUNDARY_REPEL: f32 = 2.0;
+pub const PICKUP_RESPAWN: u8 = 240;
+pub const PICKUP_HEALTH: f32 = 25.0;
+pub const RAPID_FIRE_LIFETIME: u8 = 160;
+pub const RAPID_FIRE_FACTOR: u8 = 4;
+pub const SHIPS_PATH: &str = "ships.ron";
+pub const MAPS_PATH: &str = "maps";
+pub const WEAPON_HEAT_MAX: u8 = 100;
+pub const WEAPON_HEAT_COOLING: u8 = 2;
+pub const SPREAD_PROJECTILES: usize = 5;
+pub const SPREAD_ANGLE: f32 = 0.08;
+pub const MINE_ARMING: u8 = 16;
+pub const BEAM_LENGTH: f32 = 1200.0;
+pub const BEAM_LIFETIME: u8 = 6;
+pub const SHIELD_FLASH: u8 = 8;
+pub const TORPEDO_FUEL: u8 = 96;
+pub const TORPEDO_LIFETIME: u8 = 160;
+pub const TORPEDO_NAVIGATION: f32 = 3.0;
+pub const TORPEDO_PURSUIT: f32 = 0.2;
+pub const FLARE_AMOUNT: i32 = 3;
+pub const FLARE_LIFETIME: u8 = 48;
+pub const FLARE_RANGE: f32 = 600.0;
+// cosine of the torpedo seeker's half angle, flares outside of it go unnoticed
+pub const FLARE_SEEKER: f32 = 0.5;
+// damage factor per hull edge, the last one is the stern where the engines are
+pub const HULL_DAMAGE_EDGES: [f32; 3] = [1.0, 1.0, 1.5];
+pub const PROJECTILE_SPEED: f32 = 30.0;
+pub const INDICATOR_MARGIN: f32 = 16.0;
+pub const INDICATOR_SIZE: f32 = 8.0;
+pub const COSMIC_DRAG: Vector2 = Vector2::new(0.1, 0.1);
+pub const COSMIC_DRAG_ROTATION: f32 = 0.002;
+pub const WELL_PLANET_RADIUS: f32 = 200.0;
+pub const WELL_PLANET_RANGE: f32 = 2000.0;
+pub const WELL_PLANET_STRENGTH: f32 = 120000.0;
+pub const WELL_BLACK_HOLE_RADIUS: f32 = 60.0;
+pub const WELL_BLACK_HOLE_RANGE: f32 = 2400.0;
+pub const WELL_BLACK_HOLE_STRENGTH: f32 = 200000.0;
+pub const WELL_PLACEMENT_ATTEMPTS: usize = 64;
+// collision layers, a collider sits on one of them and masks the ones it wants to hit
+pub const LAYER_SHIP: u16 = 1 << 0;
+pub const LAYER_PROJECTILE: u16 = 1 << 1;
+pub const LAYER_TORPEDO: u16 = 1 << 2;
+pub const LAYER_MINE: u16 = 1 << 3;
+pub const LAYER_ASTEROID: u16 = 1 << 4;
+pub const LAYER_OBJECTIVE: u16 = 1 << 5;
+pub const LAYER_PICKUP: u16 = 1 << 6;
+pub const LAYER_WELL: u16 = 1 << 7;
+pub const LAYER_OBSTACLE: u16 = 1 << 8;
+pub const LAYER_ORDNANCE: u16 = LAYER_PROJECTILE | LAYER_TORPEDO;
+pub const LAYER_ALL: u16 = u16::MAX;
+pub const BOTS_MAX: u8 = 7;
+pub const PRACTICE_BOTS: u8 = 3;
+// bots aren't network clients, they count down from the top so they never clash with a peer
+pub const BOT_CLIENT_ID: u32 = u32::MAX;
+// bots like to keep this far from their target, closer they back off and further they chase
+pub const BOT_RANGE: f32 = 400.0;
+pub const BOT_FIRE_RANGE: f32 = 900.0;
+pub const BOT_EVADE_RANGE: f32 = 600.0;
+pub const BOT_STEERING: f32 = 0.3;
+pub const EDITOR_PAN_SPEED: f32 = 800.0;
+pub const EDITOR_ZOOM_MIN: f32 = 0.02;
+pub const EDITOR_ZOOM_MAX: f32 = 2.0;
+// in pixels on screen, the further we zoom out the more of the cosmos it covers
+pub const EDITOR_PICK_RADIUS: f32 = 12.0;
+pub const EDITOR_OBSTACLE_SIZE: f32 = 400.0;
@@ -1,59 +1,225 @@
-use std::collections::HashMap;
-
 use crate::components::*;
 
 use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+mod archetypes;
+#[cfg(test)]
+mod tests;
+
+pub use self::archetypes::*;
+
+// every kind of entity, listed once, all of the storage is generated from this,
+// a new kind only has to implement Archetype to show up in the generic systems
+macro_rules! archetypes {
+    ($($kind:ident($ty:ty) => $field:ident, $get:ident, $get_mut:ident;)*) => {
+        // ordered by kind and then position, the simulation relies on this to never depend on hashing
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum EntityIndex {
+            $($kind(usize),)*
+        }
+
+        pub enum Entity {
+            $($kind($ty),)*
+        }
+
+        // one vector per kind, all entities of a kind have the same components
+        pub struct Entities {
+            $(pub $field: Vec<EntityId<$ty>>,)*
+
+            slots: Vec<IdSlot>,
+            slots_free: Vec<u32>,
+            generation: u64,
+        }
+
+        impl Entities {
+            pub fn new() -> Self {
+                Self {
+                    $($field: Vec::new(),)*
+
+                    slots: Vec::new(),
+                    slots_free: Vec::new(),
+                    generation: 0,
+                }
+            }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-pub enum EntityIndex {
-    Triship(usize),
-    Projectile(usize),
-    Exhaust(usize),
-    Explosion(usize),
-    Star(usize),
-    Torpedo(usize),
+            pub fn total(&self) -> usize {
+                0 $(+ self.$field.len())*
+            }
+
+            pub fn add(&mut self, entity: Entity) -> Eid {
+                // dead entities leave their slot behind, the generation is what tells them apart
+                let slot = match self.slots_free.pop() {
+                    Some(slot) => slot,
+                    None => {
+                        self.slots.push(IdSlot {
+                            generation: 0,
+                            eidx: None,
+                        });
+                        self.slots.len() as u32 - 1
+                    }
+                };
+
+                let id = Eid {
+                    generation: self.generation,
+                    slot,
+                };
+
+                self.generation += 1;
+
+                let eidx = match entity {
+                    $(Entity::$kind(entity) => {
+                        self.$field.push(EntityId { id, entity });
+                        EntityIndex::$kind(self.$field.len() - 1)
+                    })*
+                };
+
+                self.slots[slot as usize] = IdSlot {
+                    generation: id.generation,
+                    eidx: Some(eidx),
+                };
+
+                id
+            }
+
+            pub fn kill(&mut self, id: Eid) {
+                let Some(eidx) = self.entity(id) else {
+                    return;
+                };
+
+                let slots = &mut self.slots;
+
+                match eidx {
+                    $(EntityIndex::$kind(idx) => swap_dead(&mut self.$field, slots, eidx, idx),)*
+                }
+
+                slots[id.slot as usize].eidx = None;
+                self.slots_free.push(id.slot);
+            }
+
+            $(
+                #[allow(dead_code)]
+                pub fn $get(&self, id: Eid) -> Option<&$ty> {
+                    match self.entity(id)? {
+                        EntityIndex::$kind(idx) => Some(&self.$field[idx].entity),
+                        _ => None,
+                    }
+                }
+
+                #[allow(dead_code)]
+                pub fn $get_mut(&mut self, id: Eid) -> Option<&mut $ty> {
+                    match self.entity(id)? {
+                        EntityIndex::$kind(idx) => Some(&mut self.$field[idx].entity),
+                        _ => None,
+                    }
+                }
+            )*
+
+            // every entity in the order of their index, kind by kind
+            pub fn each(&self, mut f: impl FnMut(Eid, &dyn Archetype)) {
+                $(self.$field.iter().for_each(|x| f(x.id, &x.entity));)*
+            }
+
+            pub fn each_mut(&mut self, mut f: impl FnMut(Eid, Parts<'_>)) {
+                $(self.$field.iter_mut().for_each(|x| f(x.id, x.entity.parts()));)*
+            }
+
+            pub fn archetype(&self, eidx: EntityIndex) -> &dyn Archetype {
+                match eidx {
+                    $(EntityIndex::$kind(idx) => &self.$field[idx].entity,)*
+                }
+            }
+
+            pub fn parts(&mut self, eidx: EntityIndex) -> Parts<'_> {
+                match eidx {
+                    $(EntityIndex::$kind(idx) => self.$field[idx].entity.parts(),)*
+                }
+            }
+        }
+    };
 }
 
-pub enum Entity {
-    Triship(Triship),
-    Projectile(Projectile),
-    Exhaust(Particle),
-    Explosion(Particle),
-    Star(Particle),
-    Torpedo(Torpedo),
+archetypes! {
+    Triship(Triship) => triships, triship, triship_mut;
+    Projectile(Projectile) => projectiles, projectile, projectile_mut;
+    Exhaust(Particle) => exhausts, exhaust, exhaust_mut;
+    Explosion(Particle) => explosions, explosion, explosion_mut;
+    Star(Particle) => stars, star, star_mut;
+    Torpedo(Torpedo) => torpedoes, torpedo, torpedo_mut;
+    Flag(Flag) => flags, flag, flag_mut;
+    Base(Base) => bases, base, base_mut;
+    Asteroid(Asteroid) => asteroids, asteroid, asteroid_mut;
+    Pickup(Pickup) => pickups, pickup, pickup_mut;
+    Mine(Mine) => mines, mine, mine_mut;
+    Beam(Beam) => beams, beam, beam_mut;
+    Flare(Particle) => flares, flare, flare_mut;
+    Well(Well) => wells, well, well_mut;
+    Obstacle(Obstacle) => obstacles, obstacle, obstacle_mut;
 }
 
-pub struct EntityId<T> {
-    pub id: usize,
-    pub entity: T,
+// handle to an entity, the slot is reused once the entity is dead but the generation never is,
+// so a handle to something dead can't end up pointing at whatever took its place
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Eid {
+    // first, handles are ordered by when their entity was added
+    generation: u64,
+    slot: u32,
+}
+
+impl Eid {
+    // slots get reused, whoever indexes by it has to compare the whole handle as well
+    pub fn slot(&self) -> usize {
+        self.slot as usize
+    }
+}
+
+#[cfg(test)]
+impl Eid {
+    // never handed out, for things that don't have an owner
+    pub const NONE: Eid = Eid {
+        generation: u64::MAX,
+        slot: u32::MAX,
+    };
+}
+
+impl std::fmt::Display for Eid {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}.{}", self.slot, self.generation)
+    }
 }
 
-pub struct Entities {
-    pub triships: Vec<EntityId<Triship>>,
-    pub projectiles: Vec<EntityId<Projectile>>,
-    pub exhausts: Vec<EntityId<Particle>>,
-    pub explosions: Vec<EntityId<Particle>>,
-    pub stars: Vec<EntityId<Particle>>,
-    pub torpedoes: Vec<EntityId<Torpedo>>,
+struct IdSlot {
+    generation: u64,
+    eidx: Option<EntityIndex>,
+}
 
-    id_map: HashMap<usize, EntityIndex>,
-    id_free: usize,
+pub struct EntityId<T> {
+    pub id: Eid,
+    pub entity: T,
 }
 
 pub struct Triship {
+    pub team: u8,
     pub life: f32,
+    pub life_max: f32,
+    pub shield: Shield,
     pub body: Body<Triangle>,
     pub motion: Motion,
     pub boost: Boost,
-    pub cooldown_torpedo: Load,
+    // without dampeners the triship keeps on drifting
+    pub dampeners: bool,
+    pub primary: Slot,
+    pub secondary: Slot,
+    pub countermeasures: Countermeasures,
     pub targeting: Targeting,
+    pub effects: Vec<Effect>,
 }
 
 pub struct Projectile {
     pub damage: f32,
     pub body: Body<Rectangle>,
     pub motion: Motion,
-    pub owner_id: usize,
+    pub owner_id: Eid,
     pub life: f32,
 }
 
@@ -61,117 +227,123 @@ pub struct Torpedo {
     pub damage: f32,
     pub body: Body<Rectangle>,
     pub motion: Motion,
-    pub owner_id: usize,
+    pub owner_id: Eid,
     pub timer_inactive: u8,
+    // out of fuel it just drifts, at the end of its lifetime it self-destructs
+    pub fuel: u8,
+    pub lifetime: u8,
     pub life: f32,
-    pub target: Option<usize>,
+    pub target: Option<Eid>,
 }
 
-pub struct Particle {
-    pub random: u8,
-    pub lifetime: u8,
-    pub body: Body<Vector2>,
+pub struct Mine {
+    pub damage: f32,
+    pub body: Body<Rectangle>,
     pub motion: Motion,
+    pub owner_id: Eid,
+    pub timer_inactive: u8,
+    pub life: f32,
 }
 
-impl Entities {
-    pub fn new() -> Self {
-        Self {
-            triships: Vec::new(),
-            projectiles: Vec::new(),
-            exhausts: Vec::new(),
-            explosions: Vec::new(),
-            stars: Vec::new(),
-            torpedoes: Vec::new(),
-
-            id_map: HashMap::new(),
-            id_free: 0,
-        }
-    }
+// hitscan, the hit is resolved when fired and the beam itself is only a visual
+pub struct Beam {
+    pub damage: f32,
+    pub origin: Vector2,
+    pub end: Vector2,
+    pub target: Option<Eid>,
+    pub owner_id: Eid,
+    pub color: Color,
+    pub lifetime: Load,
+}
 
-    pub fn total(&self) -> usize {
-        self.triships.len()
-            + self.projectiles.len()
-            + self.exhausts.len()
-            + self.explosions.len()
-            + self.stars.len()
-            + self.torpedoes.len()
-    }
+pub struct Flag {
+    pub team: u8,
+    pub body: Body<Rectangle>,
+    pub home: Vector2,
+    pub carrier: Option<Eid>,
+}
 
-    pub fn add(&mut self, entity: Entity) -> usize {
-        let id = self.id_free;
+pub struct Base {
+    pub team: u8,
+    pub body: Body<Rectangle>,
+    pub score: u32,
+}
 
-        self.id_free += 1;
+pub struct Asteroid {
+    pub damage: f32,
+    pub radius: f32,
+    pub body: Body<Rock>,
+    pub motion: Motion,
+    pub life: f32,
+}
 
-        let eidx = match entity {
-            Entity::Triship(entity) => {
-                self.triships.push(EntityId { id, entity });
-                EntityIndex::Triship(self.triships.len() - 1)
-            }
-            Entity::Projectile(entity) => {
-                self.projectiles.push(EntityId { id, entity });
-                EntityIndex::Projectile(self.projectiles.len() - 1)
-            }
-            Entity::Exhaust(entity) => {
-                self.exhausts.push(EntityId { id, entity });
-                EntityIndex::Exhaust(self.exhausts.len() - 1)
-            }
-            Entity::Explosion(entity) => {
-                self.explosions.push(EntityId { id, entity });
-                EntityIndex::Explosion(self.explosions.len() - 1)
-            }
-            Entity::Star(entity) => {
-                self.stars.push(EntityId { id, entity });
-                EntityIndex::Star(self.stars.len() - 1)
-            }
-            Entity::Torpedo(entity) => {
-                self.torpedoes.push(EntityId { id, entity });
-                EntityIndex::Torpedo(self.torpedoes.len() - 1)
-            }
-        };
+// pulls everything within range towards its core, touching the core is lethal
+pub struct Well {
+    pub kind: WellKind,
+    pub radius: f32,
+    pub range: f32,
+    pub strength: f32,
+    pub body: Body<Rock>,
+}
 
-        self.id_map.insert(id, eidx);
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WellKind {
+    Planet,
+    BlackHole,
+}
 
-        id
-    }
+// static walls from the map, they never move so there's no shape to keep track of
+pub struct Obstacle {
+    pub color: Color,
+    pub polygon: Polygon,
+}
 
-    pub fn entity(&self, id: usize) -> Option<EntityIndex> {
-        match self.id_map.get(&id) {
-            Some(eidx) => Some(*eidx),
-            None => None,
-        }
-    }
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub body: Body<Rectangle>,
+    pub respawn: Load,
+}
 
-    pub fn kill(&mut self, id: usize) {
-        let map = &mut self.id_map;
-
-        if let Some(eidx) = map.get(&id) {
-            match eidx {
-                EntityIndex::Triship(idx) => swap_dead(&mut self.triships, map, *idx),
-                EntityIndex::Projectile(idx) => swap_dead(&mut self.projectiles, map, *idx),
-                EntityIndex::Exhaust(idx) => swap_dead(&mut self.exhausts, map, *idx),
-                EntityIndex::Explosion(idx) => swap_dead(&mut self.explosions, map, *idx),
-                EntityIndex::Star(idx) => swap_dead(&mut self.stars, map, *idx),
-                EntityIndex::Torpedo(idx) => swap_dead(&mut self.torpedoes, map, *idx),
-            }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PickupKind {
+    Health,
+    Boost,
+    Ammo,
+    RapidFire,
+}
+
+pub struct Particle {
+    pub random: u8,
+    pub lifetime: u8,
+    pub drag: Drag,
+    pub body: Body<Vector2>,
+    pub motion: Motion,
+}
+
+impl Entities {
+    pub fn entity(&self, id: Eid) -> Option<EntityIndex> {
+        match self.slots.get(id.slot as usize) {
+            Some(slot) if slot.generation == id.generation => slot.eidx,
+            _ => None,
         }
     }
 }
 
 fn swap_dead<T>(
     entities: &mut Vec<EntityId<T>>,
-    map: &mut HashMap<usize, EntityIndex>,
+    slots: &mut [IdSlot],
+    eidx: EntityIndex,
     idx: usize,
 ) {
     // remove the dead entity and swap it with the last one
     let dead = entities.swap_remove(idx);
 
-    let Some(dead_ref) = map.remove(&dead.id) else {
-        panic!("invalid map, missing id: {}", dead.id);
-    };
+    if slots[dead.id.slot as usize].generation != dead.id.generation {
+        panic!("invalid slots, missing id: {:?}", dead.id);
+    }
 
     // update reference for the swapped entity
     if let Some(swap) = entities.get_mut(idx) {
-        map.insert(swap.id, dead_ref);
+        slots[swap.id.slot as usize].eidx = Some(eidx);
     }
 }
@@ -0,0 +1,255 @@
+use super::*;
+
+use crate::constants::*;
+
+// what systems get to work with, the components a kind doesn't have are left out
+#[derive(Default)]
+pub struct Parts<'a> {
+    pub body: Option<&'a mut dyn Shape>,
+    pub motion: Option<&'a mut Motion>,
+    pub life: Option<f32>,
+    pub drag: Drag,
+}
+
+pub trait Archetype {
+    fn parts(&mut self) -> Parts<'_>;
+
+    // nothing collides with it while it's none
+    fn collider(&self) -> Option<&Polygon> {
+        None
+    }
+
+    fn layers(&self) -> Layers {
+        Layers::NONE
+    }
+}
+
+impl Archetype for Triship {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            body: Some(&mut self.body),
+            motion: Some(&mut self.motion),
+            life: Some(self.life),
+            drag: if self.dampeners {
+                Drag::Always
+            } else {
+                Drag::Never
+            },
+        }
+    }
+
+    fn collider(&self) -> Option<&Polygon> {
+        Some(&self.body.polygon)
+    }
+
+    fn layers(&self) -> Layers {
+        // triships fly through each other
+        Layers::new(LAYER_SHIP, LAYER_ALL & !LAYER_SHIP, Response::Push)
+    }
+}
+
+impl Archetype for Projectile {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            body: Some(&mut self.body),
+            motion: Some(&mut self.motion),
+            life: Some(self.life),
+            drag: Drag::Never,
+        }
+    }
+
+    fn collider(&self) -> Option<&Polygon> {
+        Some(&self.body.polygon)
+    }
+
+    fn layers(&self) -> Layers {
+        // let's not shoot projectiles with projectiles, it's extremely cpu intensive...
+        Layers::new(
+            LAYER_PROJECTILE,
+            LAYER_SHIP | LAYER_TORPEDO | LAYER_MINE | LAYER_ASTEROID | LAYER_WELL | LAYER_OBSTACLE,
+            Response::Stop,
+        )
+    }
+}
+
+impl Archetype for Torpedo {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            body: Some(&mut self.body),
+            motion: Some(&mut self.motion),
+            life: Some(self.life),
+            drag: Drag::Arcade,
+        }
+    }
+
+    fn collider(&self) -> Option<&Polygon> {
+        Some(&self.body.polygon)
+    }
+
+    fn layers(&self) -> Layers {
+        Layers::new(
+            LAYER_TORPEDO,
+            LAYER_SHIP | LAYER_ORDNANCE | LAYER_MINE | LAYER_ASTEROID | LAYER_WELL | LAYER_OBSTACLE,
+            Response::Stop,
+        )
+    }
+}
+
+impl Archetype for Mine {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            body: Some(&mut self.body),
+            motion: Some(&mut self.motion),
+            life: Some(self.life),
+            drag: Drag::Arcade,
+        }
+    }
+
+    fn collider(&self) -> Option<&Polygon> {
+        Some(&self.body.polygon)
+    }
+
+    fn layers(&self) -> Layers {
+        // give the layer a chance to get away before it's armed
+        if self.timer_inactive != 0 {
+            return Layers::NONE;
+        }
+
+        // only goes off on ships and gets shot down by ordnance
+        Layers::new(LAYER_MINE, LAYER_SHIP | LAYER_ORDNANCE, Response::Stop)
+    }
+}
+
+impl Archetype for Asteroid {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            body: Some(&mut self.body),
+            motion: Some(&mut self.motion),
+            life: Some(self.life),
+            drag: Drag::Never,
+        }
+    }
+
+    fn collider(&self) -> Option<&Polygon> {
+        Some(&self.body.polygon)
+    }
+
+    fn layers(&self) -> Layers {
+        // asteroids drift through each other
+        Layers::new(
+            LAYER_ASTEROID,
+            LAYER_SHIP | LAYER_ORDNANCE | LAYER_OBSTACLE,
+            Response::Push,
+        )
+    }
+}
+
+impl Archetype for Particle {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            body: Some(&mut self.body),
+            motion: Some(&mut self.motion),
+            drag: self.drag,
+            ..Default::default()
+        }
+    }
+}
+
+impl Archetype for Flag {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            body: Some(&mut self.body),
+            ..Default::default()
+        }
+    }
+
+    fn collider(&self) -> Option<&Polygon> {
+        Some(&self.body.polygon)
+    }
+
+    fn layers(&self) -> Layers {
+        Layers::new(LAYER_OBJECTIVE, LAYER_SHIP, Response::Sensor)
+    }
+}
+
+impl Archetype for Base {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            body: Some(&mut self.body),
+            ..Default::default()
+        }
+    }
+
+    fn collider(&self) -> Option<&Polygon> {
+        Some(&self.body.polygon)
+    }
+
+    fn layers(&self) -> Layers {
+        Layers::new(LAYER_OBJECTIVE, LAYER_SHIP, Response::Sensor)
+    }
+}
+
+impl Archetype for Pickup {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            body: Some(&mut self.body),
+            ..Default::default()
+        }
+    }
+
+    // can't be picked up while it's respawning
+    fn collider(&self) -> Option<&Polygon> {
+        if self.respawn.current != 0 {
+            return None;
+        }
+
+        Some(&self.body.polygon)
+    }
+
+    fn layers(&self) -> Layers {
+        Layers::new(LAYER_PICKUP, LAYER_SHIP, Response::Sensor)
+    }
+}
+
+impl Archetype for Well {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts {
+            body: Some(&mut self.body),
+            ..Default::default()
+        }
+    }
+
+    fn collider(&self) -> Option<&Polygon> {
+        Some(&self.body.polygon)
+    }
+
+    fn layers(&self) -> Layers {
+        // swallows ships and ordnance, everything else floats by
+        Layers::new(LAYER_WELL, LAYER_SHIP | LAYER_ORDNANCE, Response::Kill)
+    }
+}
+
+impl Archetype for Obstacle {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts::default()
+    }
+
+    fn collider(&self) -> Option<&Polygon> {
+        Some(&self.polygon)
+    }
+
+    fn layers(&self) -> Layers {
+        // stops anything that moves on its own, the rest is scenery
+        Layers::new(
+            LAYER_OBSTACLE,
+            LAYER_SHIP | LAYER_ORDNANCE | LAYER_ASTEROID,
+            Response::Bounce,
+        )
+    }
+}
+
+impl Archetype for Beam {
+    fn parts(&mut self) -> Parts<'_> {
+        Parts::default()
+    }
+}
@@ -0,0 +1,58 @@
+use super::*;
+use crate::{forge::Forge, settings::Physics};
+
+fn triship(forge: &Forge, entities: &mut Entities, x: f32) -> Eid {
+    let triship = forge.triship(Vector2::new(x, 0.0), 0, 0, Physics::Arcade);
+    entities.add(Entity::Triship(triship))
+}
+
+#[test]
+fn dead_handle_never_aliases() {
+    let forge = Forge::new();
+    let mut entities = Entities::new();
+
+    let dead = triship(&forge, &mut entities, 100.0);
+    entities.kill(dead);
+
+    // takes over the slot the dead one left behind
+    let alive = triship(&forge, &mut entities, 200.0);
+
+    assert_ne!(dead, alive);
+    assert!(entities.entity(dead).is_none());
+    assert!(entities.triship(dead).is_none());
+    assert_eq!(entities.entity(alive), Some(EntityIndex::Triship(0)));
+
+    // killing it again must not take anyone else with it
+    entities.kill(dead);
+    assert_eq!(entities.triships.len(), 1);
+}
+
+#[test]
+fn swap_remove_keeps_handles() {
+    let forge = Forge::new();
+    let mut entities = Entities::new();
+
+    let ids = [100.0, 200.0, 300.0].map(|x| triship(&forge, &mut entities, x));
+    entities.kill(ids[0]);
+
+    // the last one got swapped into the hole
+    assert_eq!(entities.entity(ids[2]), Some(EntityIndex::Triship(0)));
+    assert_eq!(entities.entity(ids[1]), Some(EntityIndex::Triship(1)));
+
+    for (id, x) in [(ids[1], 200.0), (ids[2], 300.0)] {
+        let e = entities.triship(id).expect("alive");
+        assert_eq!(e.body.state.new.shape.centroid().x.round(), x);
+    }
+}
+
+#[test]
+fn typed_lookup_checks_kind() {
+    let forge = Forge::new();
+    let mut entities = Entities::new();
+
+    let id = triship(&forge, &mut entities, 100.0);
+
+    assert!(entities.triship(id).is_some());
+    assert!(entities.torpedo(id).is_none());
+    assert!(entities.triship_mut(Eid::NONE).is_none());
+}
@@ -2,33 +2,42 @@ use std::ops::Add;
 
 use crate::{
     components::*,
-    constants::{STARFIELD_HEIGHT, STARFIELD_WIDTH},
+    constants::{
+        ASTEROID_RADIUS_MAX, ASTEROID_VERTEXES, BEAM_LIFETIME, FLARE_AMOUNT, FLARE_LIFETIME,
+        MINE_ARMING, PICKUP_RESPAWN, PROJECTILE_SPEED, SHIELD_FLASH, STARFIELD_HEIGHT,
+        STARFIELD_WIDTH, TEAM_COLORS, TORPEDO_FUEL, TORPEDO_LIFETIME, WEAPON_HEAT_MAX,
+        WELL_BLACK_HOLE_RADIUS, WELL_BLACK_HOLE_RANGE, WELL_BLACK_HOLE_STRENGTH,
+        WELL_PLACEMENT_ATTEMPTS, WELL_PLANET_RADIUS, WELL_PLANET_RANGE, WELL_PLANET_STRENGTH,
+    },
     entities::*,
+    maps::{Map, Placement},
+    random::Random,
+    settings::Physics,
+    ships::Ships,
 };
 
 use raylib::prelude::*;
 
-pub struct Forge {}
+pub struct Forge {
+    pub ships: Ships,
+}
 
 impl Forge {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            ships: Ships::load(),
+        }
     }
 
-    pub fn triship(&self, position: Vector2) -> Triship {
+    pub fn triship(&self, position: Vector2, team: u8, class: u8, physics: Physics) -> Triship {
+        let c = self.ships.class(class);
         let d = Direction::SOUTHEAST;
-        let w = 50.0;
-        let w3 = w / 3.0;
-        let h = 60.0;
-        let h3 = h / 3.0;
+        let (v1, v2, v3) = (c.hull[0], c.hull[1], c.hull[2]);
         let s = RotatedShape {
             shape: Triangle {
-                // v1: Vector2::new(50.0, 50.0),
-                // v2: Vector2::new(110.0, 75.0),
-                // v3: Vector2::new(50.0, 100.0),
-                v1: Vector2::new(position.x - w3, position.y - h3),
-                v2: Vector2::new(position.x + w3 * 2.0, position.y),
-                v3: Vector2::new(position.x - w3, position.y + h3),
+                v1: Vector2::new(position.x + v1.0, position.y + v1.1),
+                v2: Vector2::new(position.x + v2.0, position.y + v2.1),
+                v3: Vector2::new(position.x + v3.0, position.y + v3.1),
             },
             rotation: d,
         };
@@ -41,7 +50,24 @@ impl Forge {
         let b_gen = Generation { old: b, new: b };
 
         Triship {
-            life: 100.0,
+            team,
+            life: c.life,
+            life_max: c.life,
+            shield: Shield {
+                current: c.shield.capacity,
+                max: c.shield.capacity,
+                regen: c.shield.regen,
+                delay: Load {
+                    current: 0,
+                    max: c.shield.delay,
+                },
+                flash: Load {
+                    current: 0,
+                    max: SHIELD_FLASH,
+                },
+                edge: 0,
+                breached: false,
+            },
             body: Body {
                 state: Generation { old: s, new: s },
                 color: Color::DIMGRAY,
@@ -54,42 +80,83 @@ impl Forge {
             },
             motion: Motion {
                 velocity: Vector2::zero(),
-                speed_max: 20.0,
-                acceleration: 1.02,
+                speed_max: c.motion.speed_max,
+                acceleration: c.motion.acceleration,
                 rotation_speed: 0.0,
-                rotation_acceleration: 0.016,
-                rotation_speed_max: 0.28,
+                rotation_acceleration: c.motion.rotation_acceleration,
+                rotation_speed_max: c.motion.rotation_speed_max,
             },
             boost: Boost {
-                acceleration: 1.6,
+                acceleration: c.boost.acceleration,
                 acceleration_old: 0.0,
-                speed_max: 40.0,
+                speed_max: c.boost.speed_max,
                 speed_max_old: 0.0,
                 lifetime: Load {
                     current: 0,
-                    max: 100,
+                    max: c.boost.lifetime,
                 },
                 cooldown: Load {
                     current: 0,
-                    max: 50,
+                    max: c.boost.cooldown,
                 },
                 active: false,
             },
-            cooldown_torpedo: Load {
-                current: 0,
-                max: 20,
+            dampeners: physics == Physics::Arcade,
+            primary: self.slot(&c.loadout.primary),
+            secondary: self.slot(&c.loadout.secondary),
+            countermeasures: Countermeasures {
+                ammo: Load {
+                    current: c.countermeasures.ammo,
+                    max: c.countermeasures.ammo,
+                },
+                cooldown: Load {
+                    current: 0,
+                    max: c.countermeasures.cooldown,
+                },
             },
             targeting: Targeting {
                 eid: None,
+                attacker: None,
                 timer: Load {
                     current: 0,
-                    max: 50,
+                    max: c.targeting.timer,
                 },
                 visual: Load {
                     current: 0,
                     max: 30,
                 },
             },
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn slot(&self, names: &[String]) -> Slot {
+        let weapons = names
+            .iter()
+            .map(|x| {
+                let w = self.ships.weapon(x);
+
+                Weapon {
+                    kind: w.kind,
+                    damage: w.damage,
+                    cooldown: Load {
+                        current: 0,
+                        max: w.cooldown,
+                    },
+                    ammo: w.ammo.map(|x| Load { current: x, max: x }),
+                    heat: Heat {
+                        current: 0,
+                        max: WEAPON_HEAT_MAX,
+                        shot: w.heat,
+                        overheated: false,
+                    },
+                }
+            })
+            .collect();
+
+        Slot {
+            weapons,
+            selected: 0,
         }
     }
 
@@ -98,7 +165,8 @@ impl Forge {
         position: Vector2,
         direction: Vector2,
         initial_velocity: Vector2,
-        owner_id: usize,
+        owner_id: Eid,
+        damage: f32,
     ) -> Projectile {
         // |\
         // | \
@@ -111,40 +179,15 @@ impl Forge {
         let height = 1.0;
         let distance = direction * (width / 2.0);
         let p = Vector2::new(position.x + distance.x, position.y + distance.y);
-        let s = RotatedShape {
-            shape: Rectangle {
-                x: p.x - width / 2.0,
-                y: p.y - height / 2.0,
-                width,
-                height,
-            },
-            rotation: direction,
-        };
-        let v = s.shape.vertexes(direction);
-        let b = v.bounds();
-        let v_gen = Generation {
-            old: v.clone(),
-            new: v,
-        };
-        let b_gen = Generation { old: b, new: b };
-        let speed = 30.0;
+        let speed = PROJECTILE_SPEED;
 
         Projectile {
-            damage: 2.0,
-            body: Body {
-                state: Generation { old: s, new: s },
-                color: Color::LIGHTGOLDENRODYELLOW,
-                polygon: Polygon {
-                    dirty: false,
-                    vertexes: v_gen,
-                    bounds_real: b_gen,
-                    bounds_meld: b_gen,
-                },
-            },
+            damage,
+            body: rectangle(p, width, height, direction, Color::LIGHTGOLDENRODYELLOW),
             motion: Motion {
                 velocity: initial_velocity + direction * speed,
                 acceleration: 1.1,
-                speed_max: 30.0,
+                speed_max: speed,
                 rotation_speed: 0.0,
                 rotation_acceleration: 0.0,
                 rotation_speed_max: 0.0,
@@ -159,41 +202,200 @@ impl Forge {
         position: Vector2,
         direction: Vector2,
         initial_velocity: Vector2,
-        owner_id: usize,
-        target: Option<usize>,
+        owner_id: Eid,
+        target: Option<Eid>,
+        damage: f32,
     ) -> Torpedo {
         let width = 8.0;
         let height = 3.0;
         let distance = direction * width;
         let p = Vector2::new(position.x + distance.x, position.y + distance.y);
+        let body = rectangle(p, width, height, direction, Color::GRAY);
+        let speed = 8.0;
+
+        // we want the torp to be launched sideways,
+        // then once the timer_inactive is 0 it will start
+        // accelerating in the requested direction
+        let direction = Vector2::new(direction.y, direction.x * -1.0);
+
+        Torpedo {
+            damage,
+            body,
+            motion: Motion {
+                velocity: initial_velocity + direction * speed,
+                acceleration: 1.02, //1.16,
+                speed_max: 32.0,
+                rotation_speed: 0.0,
+                rotation_acceleration: 0.04,
+                rotation_speed_max: 0.16,
+            },
+            owner_id,
+            timer_inactive: 3,
+            fuel: TORPEDO_FUEL,
+            lifetime: TORPEDO_LIFETIME,
+            life: 1.0,
+            target,
+        }
+    }
+
+    pub fn mine(&self, position: Vector2, direction: Vector2, owner_id: Eid, damage: f32) -> Mine {
+        let width = 10.0;
+        let height = 10.0;
+
+        Mine {
+            damage,
+            body: rectangle(position, width, height, direction, Color::RED),
+            // mines are dropped behind the ship and drift to a halt
+            motion: Motion {
+                velocity: direction * -2.0,
+                acceleration: 0.0,
+                speed_max: 2.0,
+                rotation_speed: 0.02,
+                rotation_acceleration: 0.0,
+                rotation_speed_max: 0.02,
+            },
+            owner_id,
+            timer_inactive: MINE_ARMING,
+            life: 1.0,
+        }
+    }
+
+    pub fn beam(
+        &self,
+        origin: Vector2,
+        end: Vector2,
+        target: Option<Eid>,
+        owner_id: Eid,
+        damage: f32,
+    ) -> Beam {
+        Beam {
+            damage,
+            origin,
+            end,
+            target,
+            owner_id,
+            color: Color::SKYBLUE,
+            lifetime: Load {
+                current: BEAM_LIFETIME,
+                max: BEAM_LIFETIME,
+            },
+        }
+    }
+
+    pub fn flag(&self, position: Vector2, team: u8) -> Flag {
+        let width = 16.0;
+        let height = 24.0;
+        let color = TEAM_COLORS[team as usize % TEAM_COLORS.len()];
+
+        Flag {
+            team,
+            body: rectangle(position, width, height, Direction::EAST, color),
+            home: position,
+            carrier: None,
+        }
+    }
+
+    pub fn base(&self, position: Vector2, team: u8) -> Base {
+        let size = 300.0;
+        let color = TEAM_COLORS[team as usize % TEAM_COLORS.len()];
+
+        Base {
+            team,
+            body: rectangle(position, size, size, Direction::EAST, color),
+            score: 0,
+        }
+    }
+
+    pub fn pickup(&self, position: Vector2, kind: PickupKind) -> Pickup {
+        let width = 20.0;
+        let height = 20.0;
+        let color = match kind {
+            PickupKind::Health => Color::LIME,
+            PickupKind::Boost => Color::ORANGE,
+            PickupKind::Ammo => Color::VIOLET,
+            PickupKind::RapidFire => Color::MAGENTA,
+        };
+
+        Pickup {
+            kind,
+            body: rectangle(position, width, height, Direction::EAST, color),
+            respawn: Load {
+                current: 0,
+                max: PICKUP_RESPAWN,
+            },
+        }
+    }
+
+    pub fn pickups(&self, map: &Map, h: &mut dyn Random) -> Vec<Pickup> {
+        let amount = match &map.pickups {
+            Placement::Random(amount) => *amount,
+            Placement::Fixed(pickups) => {
+                return pickups
+                    .iter()
+                    .map(|(x, y, kind)| self.pickup(Vector2::new(*x, *y), *kind))
+                    .collect();
+            }
+        };
+
+        let mut pickups = Vec::new();
+        pickups.reserve_exact(amount);
+
+        for _ in 0..amount {
+            let position = Vector2::new(
+                h.random(0..map.width) as f32,
+                h.random(0..map.height) as f32,
+            );
+            let kind = match h.random(0..3) {
+                0 => PickupKind::Health,
+                1 => PickupKind::Boost,
+                2 => PickupKind::Ammo,
+                _ => PickupKind::RapidFire,
+            };
+
+            pickups.push(self.pickup(position, kind));
+        }
+
+        pickups
+    }
+
+    pub fn asteroid(
+        &self,
+        position: Vector2,
+        radius: f32,
+        velocity: Vector2,
+        rotation_speed: f32,
+        h: &mut dyn Random,
+    ) -> Asteroid {
+        let d = Direction::EAST;
+        let mut radii = [radius; ASTEROID_VERTEXES];
+
+        // jagged edges, but not too jagged since SAT expects convex polygons
+        for r in radii.iter_mut() {
+            *r *= h.random(80..100) as f32 / 100.0;
+        }
+
         let s = RotatedShape {
-            shape: Rectangle {
-                x: p.x - width / 2.0,
-                y: p.y - height / 2.0,
-                width,
-                height,
+            shape: Rock {
+                center: position,
+                radii,
             },
-            rotation: direction,
+            rotation: d,
         };
-        let v = s.shape.vertexes(direction);
+        let v = s.shape.vertexes(d);
         let b = v.bounds();
         let v_gen = Generation {
             old: v.clone(),
             new: v,
         };
         let b_gen = Generation { old: b, new: b };
-        let speed = 8.0;
-
-        // we want the torp to be launched sideways,
-        // then once the timer_inactive is 0 it will start
-        // accelerating in the requested direction
-        let direction = Vector2::new(direction.y, direction.x * -1.0);
+        let shade = h.random(90..140) as u8;
 
-        Torpedo {
-            damage: 10.0,
+        Asteroid {
+            damage: radius / 10.0,
+            radius,
             body: Body {
                 state: Generation { old: s, new: s },
-                color: Color::GRAY,
+                color: Color::new(shade, shade, shade, 255),
                 polygon: Polygon {
                     dirty: false,
                     vertexes: v_gen,
@@ -202,20 +404,193 @@ impl Forge {
                 },
             },
             motion: Motion {
-                velocity: initial_velocity + direction * speed,
-                acceleration: 1.02, //1.16,
-                speed_max: 32.0,
-                rotation_speed: 0.0,
-                rotation_acceleration: 0.04,
-                rotation_speed_max: 0.16,
+                velocity,
+                acceleration: 0.1,
+                speed_max: 4.0,
+                rotation_speed,
+                rotation_acceleration: 0.0,
+                rotation_speed_max: 0.02,
+            },
+            life: radius / 8.0,
+        }
+    }
+
+    pub fn asteroids(&self, map: &Map, h: &mut dyn Random) -> Vec<Asteroid> {
+        let mut asteroids = Vec::new();
+        asteroids.reserve_exact(map.asteroids);
+
+        let margin = margin(map, ASTEROID_RADIUS_MAX * 4.0);
+
+        for _ in 0..map.asteroids {
+            let position = Vector2::new(
+                h.random(margin..map.width - margin) as f32,
+                h.random(margin..map.height - margin) as f32,
+            );
+            let radius =
+                h.random(ASTEROID_RADIUS_MAX as i32 / 2..ASTEROID_RADIUS_MAX as i32) as f32;
+            let velocity = Vector2::new(
+                h.random(-100..100) as f32 / 100.0,
+                h.random(-100..100) as f32 / 100.0,
+            );
+            let rotation_speed = h.random(-10..10) as f32 / 1000.0;
+
+            asteroids.push(self.asteroid(position, radius, velocity, rotation_speed, h));
+        }
+
+        asteroids
+    }
+
+    pub fn well(&self, position: Vector2, kind: WellKind) -> Well {
+        let (radius, range, strength, color) = match kind {
+            WellKind::Planet => (
+                WELL_PLANET_RADIUS,
+                WELL_PLANET_RANGE,
+                WELL_PLANET_STRENGTH,
+                Color::DARKBLUE,
+            ),
+            WellKind::BlackHole => (
+                WELL_BLACK_HOLE_RADIUS,
+                WELL_BLACK_HOLE_RANGE,
+                WELL_BLACK_HOLE_STRENGTH,
+                Color::DARKPURPLE,
+            ),
+        };
+        let d = Direction::EAST;
+        let s = RotatedShape {
+            shape: Rock {
+                center: position,
+                radii: [radius; ASTEROID_VERTEXES],
+            },
+            rotation: d,
+        };
+        let v = s.shape.vertexes(d);
+        let b = v.bounds();
+        let v_gen = Generation {
+            old: v.clone(),
+            new: v,
+        };
+        let b_gen = Generation { old: b, new: b };
+
+        Well {
+            kind,
+            radius,
+            range,
+            strength,
+            body: Body {
+                state: Generation { old: s, new: s },
+                color,
+                polygon: Polygon {
+                    dirty: false,
+                    vertexes: v_gen,
+                    bounds_real: b_gen,
+                    bounds_meld: b_gen,
+                },
+            },
+        }
+    }
+
+    pub fn wells(&self, map: &Map, h: &mut dyn Random) -> Vec<Well> {
+        let amount = match &map.wells {
+            Placement::Random(amount) => *amount,
+            Placement::Fixed(wells) => {
+                return wells
+                    .iter()
+                    .map(|(x, y, kind)| self.well(Vector2::new(*x, *y), *kind))
+                    .collect();
+            }
+        };
+
+        let mut wells = Vec::new();
+        wells.reserve_exact(amount);
+
+        let margin = margin(map, WELL_BLACK_HOLE_RANGE);
+
+        for _ in 0..amount {
+            // and don't pull the flags out of the bases either, if the bases leave no room
+            // the well is left out, every peer draws the same numbers so they all leave it out
+            let position = (0..WELL_PLACEMENT_ATTEMPTS).find_map(|_| {
+                let position = Vector2::new(
+                    h.random(margin..map.width - margin) as f32,
+                    h.random(margin..map.height - margin) as f32,
+                );
+
+                map.bases
+                    .iter()
+                    .all(|(x, y)| (Vector2::new(*x, *y) - position).length() > margin as f32)
+                    .then_some(position)
+            });
+
+            let Some(position) = position else {
+                continue;
+            };
+
+            let kind = match h.random(0..1) {
+                0 => WellKind::Planet,
+                _ => WellKind::BlackHole,
+            };
+
+            wells.push(self.well(position, kind));
+        }
+
+        wells
+    }
+
+    pub fn obstacle(&self, vertexes: &[(f32, f32)]) -> Obstacle {
+        let v = vertexes
+            .iter()
+            .map(|(x, y)| Vector2::new(*x, *y))
+            .collect::<Vec<_>>();
+        let b = v.bounds();
+        let v_gen = Generation {
+            old: v.clone(),
+            new: v,
+        };
+        let b_gen = Generation { old: b, new: b };
+
+        Obstacle {
+            color: Color::LIGHTGRAY,
+            polygon: Polygon {
+                dirty: false,
+                vertexes: v_gen,
+                bounds_real: b_gen,
+                bounds_meld: b_gen,
             },
-            owner_id,
-            timer_inactive: 3,
-            life: 1.0,
-            target,
         }
     }
 
+    pub fn asteroid_fragments(&self, asteroid: &Asteroid, h: &mut dyn Random) -> Vec<Asteroid> {
+        let center = asteroid.body.state.new.shape.center;
+        let radius = asteroid.radius / 2.0;
+        let velocity = asteroid.motion.velocity;
+
+        // split perpendicular to the direction the parent was drifting in
+        let direction = if velocity.length_sqr() > 0.0 {
+            velocity.normalized()
+        } else {
+            Direction::EAST
+        };
+        let perpendicular = Vector2::new(-direction.y, direction.x);
+
+        let mut fragments = Vec::new();
+        fragments.reserve_exact(2);
+
+        for side in [-1.0, 1.0] {
+            let position = center + perpendicular * side * radius;
+            let speed = h.random(50..150) as f32 / 100.0;
+            let rotation_speed = h.random(-20..20) as f32 / 1000.0;
+
+            fragments.push(self.asteroid(
+                position,
+                radius,
+                velocity + perpendicular * side * speed,
+                rotation_speed,
+                h,
+            ));
+        }
+
+        fragments
+    }
+
     pub fn explosion(
         &self,
         position: Vector2,
@@ -241,6 +616,7 @@ impl Forge {
         Particle {
             lifetime,
             random,
+            drag: Drag::Never,
             body: Body {
                 state: Generation { old: s, new: s },
                 color,
@@ -262,20 +638,20 @@ impl Forge {
         }
     }
 
-    pub fn explosion_projectile(&self, position: Vector2, h: &mut RaylibHandle) -> Vec<Particle> {
+    pub fn explosion_projectile(&self, position: Vector2, h: &mut dyn Random) -> Vec<Particle> {
         let amount = 4;
         let mut explosion = Vec::new();
         explosion.reserve_exact(amount);
 
         for _ in 0..amount {
             let rotation = Vector2::zero();
-            let lifetime = h.get_random_value::<i32>(5..20) as u8;
-            let x = h.get_random_value::<i32>(-200..200) as f32 / 100.0;
-            let y = h.get_random_value::<i32>(-200..200) as f32 / 100.0;
+            let lifetime = h.random(5..20) as u8;
+            let x = h.random(-200..200) as f32 / 100.0;
+            let y = h.random(-200..200) as f32 / 100.0;
             let velocity = Vector2::new(x, y);
-            let acceleration = h.get_random_value::<i32>(1..10) as f32;
+            let acceleration = h.random(1..10) as f32;
             let color = explosion_color(h);
-            let random = h.get_random_value::<i32>(1..10) as u8;
+            let random = h.random(1..10) as u8;
 
             explosion.push(self.explosion(
                 position,
@@ -291,20 +667,20 @@ impl Forge {
         explosion
     }
 
-    pub fn explosion_torpedo(&self, position: Vector2, h: &mut RaylibHandle) -> Vec<Particle> {
+    pub fn explosion_torpedo(&self, position: Vector2, h: &mut dyn Random) -> Vec<Particle> {
         let amount = 32;
         let mut explosion = Vec::new();
         explosion.reserve_exact(amount);
 
         for _ in 0..amount {
             let rotation = Vector2::zero();
-            let lifetime = h.get_random_value::<i32>(5..20) as u8;
-            let x = h.get_random_value::<i32>(-200..200) as f32 / 100.0;
-            let y = h.get_random_value::<i32>(-200..200) as f32 / 100.0;
+            let lifetime = h.random(5..20) as u8;
+            let x = h.random(-200..200) as f32 / 100.0;
+            let y = h.random(-200..200) as f32 / 100.0;
             let velocity = Vector2::new(x, y);
-            let acceleration = h.get_random_value::<i32>(1..10) as f32;
+            let acceleration = h.random(1..10) as f32;
             let color = explosion_color(h);
-            let random = h.get_random_value::<i32>(1..15) as u8;
+            let random = h.random(1..15) as u8;
 
             explosion.push(self.explosion(
                 position,
@@ -320,20 +696,20 @@ impl Forge {
         explosion
     }
 
-    pub fn explosion_triship(&self, position: Vector2, h: &mut RaylibHandle) -> Vec<Particle> {
+    pub fn explosion_triship(&self, position: Vector2, h: &mut dyn Random) -> Vec<Particle> {
         let amount = 64;
         let mut explosion = Vec::new();
         explosion.reserve_exact(amount);
 
         for _ in 0..amount {
             let rotation = Vector2::zero();
-            let lifetime = h.get_random_value::<i32>(10..30) as u8;
-            let x = h.get_random_value::<i32>(-3000..3000) as f32 / 1000.0;
-            let y = h.get_random_value::<i32>(-3000..3000) as f32 / 1000.0;
+            let lifetime = h.random(10..30) as u8;
+            let x = h.random(-3000..3000) as f32 / 1000.0;
+            let y = h.random(-3000..3000) as f32 / 1000.0;
             let velocity = Vector2::new(x, y);
-            let acceleration = h.get_random_value::<i32>(1..2000) as f32 / 100.0;
+            let acceleration = h.random(1..2000) as f32 / 100.0;
             let color = explosion_color(h);
-            let random = h.get_random_value::<i32>(1..15) as u8;
+            let random = h.random(1..15) as u8;
 
             explosion.push(self.explosion(
                 position,
@@ -373,6 +749,7 @@ impl Forge {
         Particle {
             lifetime,
             random,
+            drag: Drag::Always,
             body: Body {
                 state: Generation { old: s, new: s },
                 color: Color::LIGHTSKYBLUE,
@@ -394,12 +771,41 @@ impl Forge {
         }
     }
 
+    pub fn flares(
+        &self,
+        position: Vector2,
+        rotation: Vector2,
+        initial_velocity: Vector2,
+        h: &mut dyn Random,
+    ) -> Vec<Particle> {
+        // pop them out of the stern in a fan
+        let angle = (rotation.y * -1.0).atan2(rotation.x * -1.0);
+
+        (0..FLARE_AMOUNT)
+            .map(|i| {
+                let offset = (i - FLARE_AMOUNT / 2) as f32 * 0.4;
+                let (sin, cos) = (angle + offset).sin_cos();
+                let direction = Vector2::new(cos, sin);
+                let speed = h.random(4..8) as f32;
+                let velocity = initial_velocity * 0.5 + direction * speed;
+                let random = h.random(0..255) as u8;
+
+                let mut flare =
+                    self.exhaust(position, direction, FLARE_LIFETIME, velocity, 0.0, random);
+                flare.body.color = Color::ORANGE;
+                flare.drag = Drag::Arcade;
+
+                flare
+            })
+            .collect()
+    }
+
     pub fn exhaust_afterburner(
         &self,
         position: Vector2,
         rotation: Vector2,
         initial_velocity: Vector2,
-        h: &mut RaylibHandle,
+        h: &mut dyn Random,
     ) -> Vec<Particle> {
         // 32 particles
         // 0 1 2 3 4 5 6
@@ -436,11 +842,11 @@ impl Forge {
                 .add(position);
 
                 // some random values to make it look awesome
-                let lifetime = (h.get_random_value::<i32>(0..2) + j) as u8;
-                let speed = h.get_random_value::<i32>(2..10) as f32;
+                let lifetime = (h.random(0..2) + j) as u8;
+                let speed = h.random(2..10) as f32;
                 let velocity = initial_velocity + rotation * speed;
-                let acceleration = h.get_random_value::<i32>(1..4) as f32;
-                let random = h.get_random_value::<i32>(10..20) as u8;
+                let acceleration = h.random(1..4) as f32;
+                let random = h.random(10..20) as u8;
 
                 exhaust.push(self.exhaust(pos, rotation, lifetime, velocity, acceleration, random));
             }
@@ -454,7 +860,7 @@ impl Forge {
         position: Vector2,
         rotation: Vector2,
         initial_velocity: Vector2,
-        h: &mut RaylibHandle,
+        h: &mut dyn Random,
     ) -> Vec<Particle> {
         let mut exhaust = Vec::new();
         exhaust.reserve_exact(3);
@@ -478,11 +884,11 @@ impl Forge {
                 .add(position);
 
                 // some random values to make it look awesome
-                let lifetime = (h.get_random_value::<i32>(0..4) + j) as u8;
-                let speed = h.get_random_value::<i32>(1..6) as f32;
+                let lifetime = (h.random(0..4) + j) as u8;
+                let speed = h.random(1..6) as f32;
                 let velocity = initial_velocity + rotation * speed;
-                let acceleration = h.get_random_value::<i32>(1..4) as f32;
-                let random = h.get_random_value::<i32>(10..20) as u8;
+                let acceleration = h.random(1..4) as f32;
+                let random = h.random(10..20) as u8;
 
                 exhaust.push(self.exhaust(pos, rotation, lifetime, velocity, acceleration, random));
             }
@@ -496,7 +902,7 @@ impl Forge {
         position: Vector2,
         rotation: Vector2,
         initial_velocity: Vector2,
-        h: &mut RaylibHandle,
+        h: &mut dyn Random,
     ) -> Vec<Particle> {
         let mut exhaust = Vec::new();
         exhaust.reserve_exact(5);
@@ -520,11 +926,11 @@ impl Forge {
                 .add(position);
 
                 // some random values to make it look awesome
-                let lifetime = (h.get_random_value::<i32>(0..2) + j) as u8;
-                let speed = h.get_random_value::<i32>(1..8) as f32;
+                let lifetime = (h.random(0..2) + j) as u8;
+                let speed = h.random(1..8) as f32;
                 let velocity = initial_velocity + rotation * speed;
-                let acceleration = h.get_random_value::<i32>(1..4) as f32;
-                let random = h.get_random_value::<i32>(10..20) as u8;
+                let acceleration = h.random(1..4) as f32;
+                let random = h.random(10..20) as u8;
 
                 exhaust.push(self.exhaust(pos, rotation, lifetime, velocity, acceleration, random));
             }
@@ -539,7 +945,7 @@ impl Forge {
         position_starboard: Vector2,
         rotation: Vector2,
         initial_velocity: Vector2,
-        h: &mut RaylibHandle,
+        h: &mut dyn Random,
     ) -> Vec<Particle> {
         let mut exhaust = Vec::new();
         exhaust.reserve_exact(10);
@@ -581,6 +987,7 @@ impl Forge {
         Particle {
             lifetime,
             random,
+            drag: Drag::Never,
             body: Body {
                 state: Generation { old: s, new: s },
                 color,
@@ -602,23 +1009,23 @@ impl Forge {
         }
     }
 
-    pub fn stars(&self, h: &mut RaylibHandle) -> Vec<Particle> {
-        let amount = 128;
+    pub fn stars(&self, map: &Map, h: &mut dyn Random) -> Vec<Particle> {
+        let amount = map.background.stars;
         let mut stars = Vec::new();
         stars.reserve_exact(amount);
 
         for _ in 0..amount {
             let rotation = Vector2::zero();
             let position = Vector2::new(
-                h.get_random_value::<i32>(1..STARFIELD_WIDTH - 1) as f32,
-                h.get_random_value::<i32>(1..STARFIELD_HEIGHT - 1) as f32,
+                h.random(1..STARFIELD_WIDTH - 1) as f32,
+                h.random(1..STARFIELD_HEIGHT - 1) as f32,
             );
             let lifetime = 0;
             // 1 in 9 will be moving slightly
-            let velocity = if h.get_random_value::<i32>(0..8) > 7 {
+            let velocity = if h.random(0..8) > 7 {
                 Vector2::new(
-                    h.get_random_value::<i32>(-50..50) as f32 / 1000.0,
-                    h.get_random_value::<i32>(-50..50) as f32 / 1000.0,
+                    h.random(-50..50) as f32 / 1000.0,
+                    h.random(-50..50) as f32 / 1000.0,
                 )
             } else {
                 Vector2::zero()
@@ -626,13 +1033,12 @@ impl Forge {
 
             let acceleration = 0.0;
             let color = Color::new(
-                h.get_random_value::<i32>(100..255) as u8,
-                h.get_random_value::<i32>(200..255) as u8,
-                h.get_random_value::<i32>(200..255) as u8,
-                h.get_random_value::<i32>(0..255) as u8,
+                h.random(100..255) as u8,
+                h.random(200..255) as u8,
+                h.random(200..255) as u8,
+                h.random(0..255) as u8,
             );
-            let random =
-                ((h.get_random_value::<i32>(0..7) << 1) + h.get_random_value::<i32>(0..1)) as u8;
+            let random = ((h.random(0..7) << 1) + h.random(0..1)) as u8;
 
             stars.push(self.star(
                 position,
@@ -649,11 +1055,54 @@ impl Forge {
     }
 }
 
-fn explosion_color(h: &mut RaylibHandle) -> Color {
+// keep the edges clear when scattering things, that's where the players usually spawn,
+// small maps still need some room left in the middle
+fn margin(map: &Map, wanted: f32) -> i32 {
+    (wanted as i32).min(map.width.min(map.height) / 4)
+}
+
+// a rectangle centered on the position, everything that isn't a ship or a rock starts out as one
+fn rectangle(
+    position: Vector2,
+    width: f32,
+    height: f32,
+    rotation: Vector2,
+    color: Color,
+) -> Body<Rectangle> {
+    let s = RotatedShape {
+        shape: Rectangle {
+            x: position.x - width / 2.0,
+            y: position.y - height / 2.0,
+            width,
+            height,
+        },
+        rotation,
+    };
+    let v = s.shape.vertexes(rotation);
+    let b = v.bounds();
+    let v_gen = Generation {
+        old: v.clone(),
+        new: v,
+    };
+    let b_gen = Generation { old: b, new: b };
+
+    Body {
+        state: Generation { old: s, new: s },
+        color,
+        polygon: Polygon {
+            dirty: false,
+            vertexes: v_gen,
+            bounds_real: b_gen,
+            bounds_meld: b_gen,
+        },
+    }
+}
+
+fn explosion_color(h: &mut dyn Random) -> Color {
     Color {
-        r: h.get_random_value::<i32>(250..255) as u8,
-        g: h.get_random_value::<i32>(0..8) as u8,
-        b: h.get_random_value::<i32>(0..0) as u8,
-        a: h.get_random_value::<i32>(100..200) as u8,
+        r: h.random(250..255) as u8,
+        g: h.random(0..8) as u8,
+        b: h.random(0..0) as u8,
+        a: h.random(100..200) as u8,
     }
 }
@@ -1,31 +1,51 @@
+pub mod bots;
+mod boundary;
 mod collisions;
-
-use std::collections::BTreeSet;
+#[cfg(test)]
+pub mod harness;
+pub mod objectives;
+mod pickups;
+mod shields;
+#[cfg(test)]
+mod tests;
+mod weapons;
+
+use std::{
+    collections::BTreeSet,
+    f32::consts::{PI, TAU},
+};
 
 use crate::{
     bus::Bus,
     commands::{Command, EntityCommands},
-    components::{Centroidable, Generationable, Motion, Renewable, Shape, Targeting},
-    constants::{COSMOS_HEIGHT, COSMOS_WIDTH, STARFIELD_HEIGHT, STARFIELD_WIDTH},
-    entities::{Entities, EntityIndex},
+    components::{Centroidable, Drag, Generationable, Motion, Renewable, Targeting},
+    constants::{
+        COSMIC_DRAG, COSMIC_DRAG_ROTATION, FLARE_RANGE, FLARE_SEEKER, STARFIELD_HEIGHT,
+        STARFIELD_WIDTH, TORPEDO_NAVIGATION, TORPEDO_PURSUIT,
+    },
+    entities::{Eid, Entities, Entity, EntityIndex},
     forge::Forge,
+    maps::Map,
     messages::LogicMessage,
     quadtree::QuadTree,
+    random::Random,
+    settings::{Physics, Settings},
     utils::generate_targeting_area,
 };
 
 use raylib::prelude::*;
 
+use boundary::*;
 use collisions::*;
-
-// TODO: move to constants..?
-const COSMIC_DRAG: Vector2 = Vector2::new(0.1, 0.1);
-const COSMIC_DRAG_ROTATION: f32 = 0.002;
+use objectives::*;
+use pickups::*;
+use shields::*;
+use weapons::*;
 
 pub struct Logic {
-    dead: BTreeSet<usize>,
-    collisions: Vec<(EntityIndex, EntityIndex)>,
-    commands: Vec<(usize, Command)>,
+    dead: BTreeSet<Eid>,
+    collisions: Vec<(EntityIndex, EntityIndex, Contact)>,
+    commands: Vec<(Eid, Command)>,
 }
 
 impl Logic {
@@ -44,7 +64,9 @@ impl Logic {
         entity_cmds: &[EntityCommands],
         forge: &Forge,
         quadtree: &mut QuadTree,
-        h: &mut RaylibHandle,
+        settings: &Settings,
+        map: &Map,
+        h: &mut dyn Random,
     ) {
         let dead = &mut self.dead;
         let commands = &mut self.commands;
@@ -52,22 +74,32 @@ impl Logic {
 
         update_dead_removal(entities, dead);
         update_body_generation(entities);
-        update_commands(entities, entity_cmds, commands, forge, quadtree, h);
+        update_commands(entities, entity_cmds, commands, forge, settings, h);
         update_boost(entities);
-        update_cooldowns(entities);
-        update_motion(entities);
+        update_weapons(entities);
+        update_countermeasures(entities);
+        update_shields(entities);
+        update_effects(entities);
+        update_pickups(entities);
+        update_motion(entities, settings.physics);
         update_body(entities);
+        update_boundary(entities, settings.boundary, map);
+        update_flags(entities);
+        update_asteroids_bounce(entities, map);
         update_collision_detection(entities, quadtree, collisions);
         update_collision_reaction(entities, collisions, forge, h);
+        update_beams(entities, dead, forge, h);
         update_targeting_target(entities);
         update_particles_exhaust_alpha(entities);
         update_particles_lifetime(entities, dead);
         update_particles_explosions(entities);
         update_particles_stars(entities);
-        update_torpedo_timers(entities);
+        update_torpedo_timers(entities, forge, h);
+        update_flares(entities);
+        update_mine_timers(entities);
         update_targeting_tracking(entities, commands);
         update_commands_accelerate(entities, commands);
-        update_out_of_bounds(entities, dead);
+        update_out_of_bounds(entities, dead, map);
         update_dead_detection(entities, dead);
         update_dead_notify(entities, dead, bus);
     }
@@ -75,50 +107,39 @@ impl Logic {
 
 // TODO: move these functions into their own files? need to figure out structure
 
-fn update_targeting_tracking(entities: &mut Entities, commands: &mut Vec<(usize, Command)>) {
+fn update_targeting_tracking(entities: &mut Entities, commands: &mut Vec<(Eid, Command)>) {
     let targeter_target = entities
         .torpedoes
         .iter()
         .filter_map(|x| match x.entity.target {
-            Some(target) if x.entity.timer_inactive == 0 => Some((x.id, target)),
+            // no fuel means no steering either
+            Some(target) if x.entity.timer_inactive == 0 && x.entity.fuel != 0 => {
+                Some((x.id, target))
+            }
             _ => None,
         })
         .collect::<Vec<_>>();
 
     targeter_target.iter().for_each(|(eid, eid_target)| {
-        let (eidx, rotation, centroid, acceleration, velocity, speed_max, rotation_speed) =
-            match entities.entity(*eid) {
-                Some(eidx) => match eidx {
-                    EntityIndex::Torpedo(idx) => {
-                        let e = &entities.torpedoes[idx].entity;
-                        (
-                            eidx,
-                            e.body.state.new.rotation,
-                            e.body.state.new.shape.centroid(),
-                            e.motion.acceleration,
-                            e.motion.velocity,
-                            e.motion.speed_max,
-                            e.motion.rotation_speed,
-                        )
-                    }
-                    _ => panic!("wtf targeter {:?}", eidx),
-                },
-                None => panic!("wtf target"),
-            };
-
-        let eidx_target = match entities.entity(*eid_target) {
-            Some(eidx) => eidx,
-            None => {
-                // target is dead, stop following
-                let target = match eidx {
-                    EntityIndex::Torpedo(idx) => &mut entities.torpedoes[idx].entity.target,
-                    _ => panic!("wtf target {:?}", eidx),
-                };
-
-                *target = None;
+        let Some(e) = entities.torpedo(*eid) else {
+            return;
+        };
 
-                return;
+        let rotation = e.body.state.new.rotation;
+        let centroid = e.body.state.new.shape.centroid();
+        let acceleration = e.motion.acceleration;
+        let velocity = e.motion.velocity;
+        let speed_max = e.motion.speed_max;
+        let rotation_speed = e.motion.rotation_speed;
+        let rotation_acceleration = e.motion.rotation_acceleration;
+
+        let Some(eidx_target) = entities.entity(*eid_target) else {
+            // target is dead, stop following
+            if let Some(e) = entities.torpedo_mut(*eid) {
+                e.target = None;
             }
+
+            return;
         };
 
         let (
@@ -138,7 +159,38 @@ fn update_targeting_tracking(entities: &mut Entities, commands: &mut Vec<(usize,
                     e.motion.rotation_speed,
                 )
             }
-            _ => panic!("wtf target centroid {:?}", eidx_target),
+            EntityIndex::Torpedo(idx) => {
+                let e = &entities.torpedoes[idx].entity;
+                (
+                    e.body.state.new.shape.centroid(),
+                    e.motion.velocity,
+                    e.motion.acceleration,
+                    e.body.state.new.rotation,
+                    e.motion.rotation_speed,
+                )
+            }
+            EntityIndex::Flag(idx) => {
+                let e = &entities.flags[idx].entity;
+                (
+                    e.body.state.new.shape.centroid(),
+                    Vector2::zero(),
+                    0.0,
+                    e.body.state.new.rotation,
+                    0.0,
+                )
+            }
+            EntityIndex::Flare(idx) => {
+                let e = &entities.flares[idx].entity;
+                (
+                    e.body.state.new.shape,
+                    e.motion.velocity,
+                    0.0,
+                    e.body.state.new.rotation,
+                    0.0,
+                )
+            }
+            // nothing we know how to follow
+            _ => return,
         };
 
         // works without predictions, but much smoother with them :)
@@ -155,17 +207,25 @@ fn update_targeting_tracking(entities: &mut Entities, commands: &mut Vec<(usize,
             velocity_target + predicted_rotation_target * acceleration_target;
         let predicted_centroid_target = centroid_target + predicted_velocity_target;
 
-        let centroid_delta = predicted_centroid_target - predicted_centroid;
-        let rotation_delta = centroid_delta.normalized();
-        let threshold = 0.01;
-        let desired_velocity = rotation_delta * speed_max;
-        let error = desired_velocity - predicted_velocity + predicted_velocity_target;
-        let error = error.normalized();
-        let cross = error.x * predicted_rotation.y - error.y * predicted_rotation.x;
+        let los = predicted_centroid_target - predicted_centroid;
+        let closing = predicted_velocity_target - predicted_velocity;
 
-        if cross < -threshold {
+        // proportional navigation, turn N times as fast as the line of sight is rotating
+        let los_rate = (los.x * closing.y - los.y * closing.x) / los.length_sqr().max(1.0);
+
+        // lead pursuit, head for where the target will be once we get there,
+        // this gets us on a collision course when launched sideways or behind
+        let time = los.length() / speed_max;
+        let lead = los + predicted_velocity_target * time;
+        let heading = predicted_rotation.y.atan2(predicted_rotation.x);
+        let error = (lead.y.atan2(lead.x) - heading + PI).rem_euclid(TAU) - PI;
+
+        let desired = TORPEDO_NAVIGATION * los_rate + TORPEDO_PURSUIT * error;
+        let threshold = rotation_acceleration / 2.0;
+
+        if desired > rotation_speed + threshold {
             commands.push((*eid, Command::RotateRight));
-        } else if cross > threshold {
+        } else if desired < rotation_speed - threshold {
             commands.push((*eid, Command::RotateLeft));
         }
     });
@@ -183,55 +243,44 @@ fn update_targeting_target(entities: &mut Entities) {
         .collect::<Vec<_>>();
 
     targeter_target.iter().for_each(|(eid, eid_target)| {
-        let eidx_target = entities.entity(*eid_target);
-        let (eidx, centroid) = match entities.entity(*eid) {
-            Some(eidx) => match eidx {
-                EntityIndex::Triship(idx) => {
-                    let e = &entities.triships[idx].entity;
-                    (eidx, e.body.state.new.shape.centroid())
-                }
-                _ => panic!("wtf targeter {:?}", eidx),
-            },
-            None => panic!("wtf target {}", eid),
+        let bounds_target = entities
+            .entity(*eid_target)
+            .and_then(|x| entities.archetype(x).collider())
+            .map(|x| x.bounds_meld.new);
+
+        let Some(e) = entities.triship_mut(*eid) else {
+            return;
         };
 
+        let centroid = e.body.state.new.shape.centroid();
+        let targeting = &mut e.targeting;
+
         // update visuals
-        let targeting = target(entities, eidx);
         if targeting.visual.current == targeting.visual.max {
             targeting.visual.current = 0;
         } else {
             targeting.visual.current += 1;
         }
 
-        let bounds_target = &match eidx_target {
-            Some(eidx_target) => {
-                // target has already been locked
-                if target(entities, eidx).timer.current == 0 {
-                    return;
-                }
-
-                match eidx_target {
-                    EntityIndex::Triship(idx) => &entities.triships[idx].entity.body.polygon,
-                    _ => panic!("wtf target area {:?}", eidx_target),
-                }
-                .bounds_meld
-                .new
-            }
-            None => {
-                // target is dead
-                reset(target(entities, eidx));
-                return;
-            }
+        let Some(bounds_target) = bounds_target else {
+            // target is dead
+            reset(targeting);
+            return;
         };
 
+        // target has already been locked
+        if targeting.timer.current == 0 {
+            return;
+        }
+
         let targeting_area = generate_targeting_area(centroid);
 
-        if targeting_area.check_collision_recs(bounds_target) {
+        if targeting_area.check_collision_recs(&bounds_target) {
             // target is still within reach
-            lock(target(entities, eidx));
+            lock(targeting);
         } else {
             // target has been lost
-            reset(target(entities, eidx));
+            reset(targeting);
         }
     });
 
@@ -247,37 +296,107 @@ fn update_targeting_target(entities: &mut Entities) {
 
         target.timer.current -= 1;
     }
+}
+
+fn update_torpedo_timers(entities: &mut Entities, forge: &Forge, h: &mut dyn Random) {
+    let mut expired = Vec::new();
+
+    entities.torpedoes.iter_mut().for_each(|x| {
+        let e = &mut x.entity;
+
+        if e.timer_inactive != 0 {
+            e.timer_inactive -= 1;
+        } else if e.fuel != 0 {
+            e.fuel -= 1;
+        }
+
+        if e.lifetime != 0 {
+            e.lifetime -= 1;
+            return;
+        }
+
+        // self-destruct
+        if e.life > 0.0 {
+            e.life = 0.0;
+            expired.push(e.body.state.new.shape.centroid());
+        }
+    });
 
-    fn target(entities: &mut Entities, eidx: EntityIndex) -> &mut Targeting {
-        match eidx {
-            EntityIndex::Triship(idx) => &mut entities.triships[idx].entity.targeting,
-            _ => panic!("wtf target {:?}", eidx),
+    for c in expired {
+        for explosion in forge.explosion_torpedo(c, h) {
+            entities.add(Entity::Explosion(explosion));
         }
     }
 }
 
-fn update_torpedo_timers(entities: &mut Entities) {
+// flares that are closer than the current target and within the seeker's view steal the lock
+fn update_flares(entities: &mut Entities) {
+    let flares = entities
+        .flares
+        .iter()
+        .map(|x| (x.id, x.entity.body.state.new.shape))
+        .collect::<Vec<_>>();
+
+    if flares.is_empty() {
+        return;
+    }
+
+    let targets = entities
+        .torpedoes
+        .iter()
+        .map(|x| {
+            let target = x.entity.target?;
+            let centroid = match entities.entity(target)? {
+                EntityIndex::Triship(idx) => entities.triships[idx]
+                    .entity
+                    .body
+                    .state
+                    .new
+                    .shape
+                    .centroid(),
+                EntityIndex::Flare(idx) => entities.flares[idx].entity.body.state.new.shape,
+                _ => return None,
+            };
+
+            Some(centroid)
+        })
+        .collect::<Vec<_>>();
+
     entities
         .torpedoes
         .iter_mut()
-        .filter_map(|x| {
-            if x.entity.timer_inactive != 0 {
-                Some(&mut x.entity.timer_inactive)
-            } else {
-                None
+        .zip(targets)
+        .filter_map(|(x, centroid_target)| Some((x, centroid_target?)))
+        .for_each(|(x, centroid_target)| {
+            let e = &mut x.entity;
+            let centroid = e.body.state.new.shape.centroid();
+            let rotation = e.body.state.new.rotation;
+            let mut dist_min = (centroid_target - centroid).length_sqr();
+
+            for (eid, flare) in flares.iter() {
+                let delta = *flare - centroid;
+                let dist = delta.length_sqr();
+
+                if dist > FLARE_RANGE.powi(2) || dist >= dist_min {
+                    continue;
+                }
+
+                if rotation.dot(delta.normalized()) < FLARE_SEEKER {
+                    continue;
+                }
+
+                dist_min = dist;
+                e.target = Some(*eid);
             }
-        })
-        .for_each(|x| {
-            *x -= 1;
-        })
+        });
 }
 
-fn update_commands_accelerate(entities: &mut Entities, commands: &mut Vec<(usize, Command)>) {
+fn update_commands_accelerate(entities: &mut Entities, commands: &mut Vec<(Eid, Command)>) {
     entities
         .torpedoes
         .iter()
         .filter_map(|x| {
-            if x.entity.timer_inactive == 0 {
+            if x.entity.timer_inactive == 0 && x.entity.fuel != 0 {
                 Some((x.id, x.entity.life))
             } else {
                 None
@@ -290,15 +409,6 @@ fn update_commands_accelerate(entities: &mut Entities, commands: &mut Vec<(usize
         });
 }
 
-fn update_cooldowns(entities: &mut Entities) {
-    entities
-        .triships
-        .iter_mut()
-        .map(|x| &mut x.entity.cooldown_torpedo.current)
-        .filter(|x| **x != 0)
-        .for_each(|x| *x -= 1);
-}
-
 fn update_particles_stars(entities: &mut Entities) {
     entities.stars.iter_mut().for_each(|x| {
         // 0b_0000_0000
@@ -381,7 +491,7 @@ fn update_particles_explosions(entities: &mut Entities) {
     });
 }
 
-fn update_dead_notify(entities: &mut Entities, dead: &BTreeSet<usize>, bus: &mut Bus) {
+fn update_dead_notify(entities: &mut Entities, dead: &BTreeSet<Eid>, bus: &mut Bus) {
     for eid in dead {
         if let Some(eidx) = entities.entity(*eid) {
             bus.send(LogicMessage::EntityDead(*eid, eidx));
@@ -389,17 +499,12 @@ fn update_dead_notify(entities: &mut Entities, dead: &BTreeSet<usize>, bus: &mut
     }
 }
 
-fn update_dead_detection(entities: &mut Entities, dead: &mut BTreeSet<usize>) {
-    entities
-        .triships
-        .iter()
-        .map(|x| (x.id, x.entity.life))
-        .chain(entities.projectiles.iter().map(|x| (x.id, x.entity.life)))
-        .chain(entities.torpedoes.iter().map(|x| (x.id, x.entity.life)))
-        .filter_map(|(id, life)| if life <= 0.0 { Some(id) } else { None })
-        .for_each(|id| {
+fn update_dead_detection(entities: &mut Entities, dead: &mut BTreeSet<Eid>) {
+    entities.each_mut(|id, parts| {
+        if matches!(parts.life, Some(life) if life <= 0.0) {
             dead.insert(id);
-        });
+        }
+    });
 }
 
 fn update_boost(entities: &mut Entities) {
@@ -447,11 +552,12 @@ fn update_particles_exhaust_alpha(entities: &mut Entities) {
     });
 }
 
-fn update_particles_lifetime(entities: &mut Entities, dead: &mut BTreeSet<usize>) {
+fn update_particles_lifetime(entities: &mut Entities, dead: &mut BTreeSet<Eid>) {
     entities
         .exhausts
         .iter_mut()
         .chain(entities.explosions.iter_mut())
+        .chain(entities.flares.iter_mut())
         .for_each(|x| {
             if x.entity.lifetime == 0 {
                 dead.insert(x.id);
@@ -461,112 +567,103 @@ fn update_particles_lifetime(entities: &mut Entities, dead: &mut BTreeSet<usize>
         })
 }
 
-fn update_dead_removal(entities: &mut Entities, dead: &mut BTreeSet<usize>) {
+fn update_dead_removal(entities: &mut Entities, dead: &mut BTreeSet<Eid>) {
     while let Some(d) = dead.pop_first() {
         entities.kill(d);
     }
 }
 
 fn update_body_generation(entities: &mut Entities) {
-    entities
-        .triships
-        .iter_mut()
-        .map(|x| &mut x.entity.body as &mut dyn Generationable)
-        .chain(
-            entities
-                .projectiles
-                .iter_mut()
-                .map(|x| &mut x.entity.body as &mut dyn Generationable),
-        )
-        .chain(
-            entities
-                .exhausts
-                .iter_mut()
-                .map(|x| &mut x.entity.body as &mut dyn Generationable),
-        )
-        .chain(
-            entities
-                .explosions
-                .iter_mut()
-                .map(|x| &mut x.entity.body as &mut dyn Generationable),
-        )
-        .chain(
-            entities
-                .stars
-                .iter_mut()
-                .map(|x| &mut x.entity.body as &mut dyn Generationable),
-        )
-        .chain(
-            entities
-                .torpedoes
-                .iter_mut()
-                .map(|x| &mut x.entity.body as &mut dyn Generationable),
-        )
-        .for_each(|body| body.generation());
+    entities.each_mut(|_, parts| {
+        if let Some(body) = parts.body {
+            body.generation();
+        }
+    });
 }
 
 fn update_commands(
     entities: &mut Entities,
     entity_cmds: &[EntityCommands],
-    entity_cmds_internal: &mut Vec<(usize, Command)>,
+    entity_cmds_internal: &mut Vec<(Eid, Command)>,
     forge: &Forge,
-    quadtree: &QuadTree,
-    h: &mut RaylibHandle,
+    settings: &Settings,
+    h: &mut dyn Random,
 ) {
     for entity_cmd in entity_cmds {
         for cmd in entity_cmd.commands.iter() {
-            cmd.execute(entities, entity_cmd.id, forge, quadtree, h);
+            cmd.execute(entities, entity_cmd.id, forge, settings, h);
         }
     }
 
     while let Some((id, cmd)) = entity_cmds_internal.pop() {
-        cmd.execute(entities, id, forge, quadtree, h);
+        cmd.execute(entities, id, forge, settings, h);
     }
 }
 
-fn update_motion(entities: &mut Entities) {
-    entities
-        .triships
-        .iter_mut()
-        .map(|x| (&mut x.entity.motion, true))
-        .chain(
-            entities
-                .projectiles
-                .iter_mut()
-                .map(|x| (&mut x.entity.motion, false)),
-        )
-        .chain(
-            entities
-                .exhausts
-                .iter_mut()
-                .map(|x| (&mut x.entity.motion, true)),
-        )
-        .chain(
-            entities
-                .explosions
-                .iter_mut()
-                .map(|x| (&mut x.entity.motion, false)),
-        )
-        .chain(
-            entities
-                .stars
-                .iter_mut()
-                .map(|x| (&mut x.entity.motion, false)),
-        )
-        .chain(
-            entities
-                .torpedoes
-                .iter_mut()
-                .map(|x| (&mut x.entity.motion, true)),
-        )
-        .for_each(|(motion, apply_drag)| {
-            if apply_drag {
-                apply_cosmic_drag(motion);
-            }
+fn update_motion(entities: &mut Entities, physics: Physics) {
+    // in newtonian space only the dampeners of a triship will slow it down,
+    // exhaust particles still fade out the same way to keep them looking nice
+    let drag = physics == Physics::Arcade;
 
-            check_speed_max(motion);
-            check_rotation_speed_max(motion);
-        });
+    apply_gravity(entities);
+
+    entities.each_mut(|_, parts| {
+        let Some(motion) = parts.motion else {
+            return;
+        };
+
+        if parts.drag == Drag::Always || (parts.drag == Drag::Arcade && drag) {
+            apply_cosmic_drag(motion);
+        }
+
+        check_speed_max(motion);
+        check_rotation_speed_max(motion);
+    });
+
+    fn apply_gravity(entities: &mut Entities) {
+        let wells = entities
+            .wells
+            .iter()
+            .map(|x| {
+                let e = &x.entity;
+                (e.body.state.new.shape.center, e.radius, e.range, e.strength)
+            })
+            .collect::<Vec<_>>();
+
+        if wells.is_empty() {
+            return;
+        }
+
+        entities
+            .triships
+            .iter_mut()
+            .map(|x| {
+                let e = &mut x.entity;
+                (e.body.state.new.shape.centroid(), &mut e.motion)
+            })
+            .chain(entities.projectiles.iter_mut().map(|x| {
+                let e = &mut x.entity;
+                (e.body.state.new.shape.centroid(), &mut e.motion)
+            }))
+            .chain(entities.torpedoes.iter_mut().map(|x| {
+                let e = &mut x.entity;
+                (e.body.state.new.shape.centroid(), &mut e.motion)
+            }))
+            .for_each(|(centroid, motion)| {
+                for (center, radius, range, strength) in wells.iter() {
+                    let d = *center - centroid;
+                    let dist_sqr = d.length_sqr();
+
+                    if dist_sqr > range * range {
+                        continue;
+                    }
+
+                    // inverse square, capped at the core so we don't fling anything across the cosmos
+                    let pull = strength / dist_sqr.max(radius * radius);
+                    motion.velocity += d.normalized() * pull;
+                }
+            });
+    }
 
     fn apply_cosmic_drag(motion: &mut Motion) {
         let direction = motion.velocity.normalized();
@@ -616,48 +713,38 @@ fn update_motion(entities: &mut Entities) {
 }
 
 fn update_body(entities: &mut Entities) {
-    entities
-        .triships
-        .iter_mut()
-        .map(|x| (&mut x.entity.body as &mut dyn Shape, &x.entity.motion))
-        .chain(
-            entities
-                .projectiles
-                .iter_mut()
-                .map(|x| (&mut x.entity.body as &mut dyn Shape, &x.entity.motion)),
-        )
-        .chain(
-            entities
-                .exhausts
-                .iter_mut()
-                .map(|x| (&mut x.entity.body as &mut dyn Shape, &x.entity.motion)),
-        )
-        .chain(
-            entities
-                .explosions
-                .iter_mut()
-                .map(|x| (&mut x.entity.body as &mut dyn Shape, &x.entity.motion)),
-        )
-        .chain(
-            entities
-                .stars
-                .iter_mut()
-                .map(|x| (&mut x.entity.body as &mut dyn Shape, &x.entity.motion)),
-        )
-        .chain(
-            entities
-                .torpedoes
-                .iter_mut()
-                .map(|x| (&mut x.entity.body as &mut dyn Shape, &x.entity.motion)),
-        )
-        .for_each(|(shape, motion)| {
-            shape.accelerate(motion.velocity);
-            shape.rotate(motion.rotation_speed);
-            shape.renew();
-        });
+    entities.each_mut(|_, parts| {
+        let (Some(shape), Some(motion)) = (parts.body, parts.motion) else {
+            return;
+        };
+
+        shape.accelerate(motion.velocity);
+        shape.rotate(motion.rotation_speed);
+        shape.renew();
+    });
 }
 
-fn update_out_of_bounds(entities: &mut Entities, dead: &mut BTreeSet<usize>) {
+fn update_asteroids_bounce(entities: &mut Entities, map: &Map) {
+    entities.asteroids.iter_mut().for_each(|x| {
+        let bounds = x.entity.body.polygon.bounds_real.new;
+        let velocity = &mut x.entity.motion.velocity;
+
+        // asteroids drift forever, keep them within the cosmos by bouncing off the edges
+        if (bounds.x < 0.0 && velocity.x < 0.0)
+            || (bounds.x + bounds.width > map.width as f32 && velocity.x > 0.0)
+        {
+            velocity.x *= -1.0;
+        }
+
+        if (bounds.y < 0.0 && velocity.y < 0.0)
+            || (bounds.y + bounds.height > map.height as f32 && velocity.y > 0.0)
+        {
+            velocity.y *= -1.0;
+        }
+    });
+}
+
+fn update_out_of_bounds(entities: &mut Entities, dead: &mut BTreeSet<Eid>, map: &Map) {
     entities
         .projectiles
         .iter()
@@ -668,11 +755,17 @@ fn update_out_of_bounds(entities: &mut Entities, dead: &mut BTreeSet<usize>) {
                 .iter()
                 .map(|x| (x.id, x.entity.body.polygon.bounds_real.new)),
         )
+        .chain(
+            entities
+                .mines
+                .iter()
+                .map(|x| (x.id, x.entity.body.polygon.bounds_real.new)),
+        )
         .for_each(|(id, bounds)| {
             if bounds.x + bounds.width < 0.0
-                || bounds.x > COSMOS_WIDTH as f32
+                || bounds.x > map.width as f32
                 || bounds.y + bounds.height < 0.0
-                || bounds.y > COSMOS_HEIGHT as f32
+                || bounds.y > map.height as f32
             {
                 dead.insert(id);
             }
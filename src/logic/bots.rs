@@ -0,0 +1,185 @@
+use std::f32::consts::{PI, TAU};
+
+use crate::{
+    commands::Command,
+    components::{Centroidable, WeaponKind},
+    constants::{BOT_EVADE_RANGE, BOT_FIRE_RANGE, BOT_RANGE, BOT_STEERING, PROJECTILE_SPEED},
+    entities::{Eid, Entities},
+    settings::Difficulty,
+    utils::generate_targeting_area,
+};
+
+// bots only look at the simulation and answer with commands, every peer runs them
+// on the same state and ends up with the same commands, nothing goes over the network
+pub struct Bot {
+    pub difficulty: Difficulty,
+    // bots don't react every tick, the last decision is held until it's time to think again,
+    // only the ones meant to be held down though, cycling or locking would go around in circles
+    timer: u8,
+    held: Vec<Command>,
+}
+
+impl Bot {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            timer: 0,
+            held: Vec::new(),
+        }
+    }
+
+    pub fn commands(&mut self, eid: Eid, entities: &Entities) -> Box<[Command]> {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return self.held.clone().into_boxed_slice();
+        }
+
+        let commands = think(eid, entities, self.difficulty);
+
+        self.timer = reaction(self.difficulty) - 1;
+        self.held = commands.iter().copied().filter(held).collect();
+
+        commands.into_boxed_slice()
+    }
+}
+
+fn think(eid: Eid, entities: &Entities, difficulty: Difficulty) -> Vec<Command> {
+    let mut commands = Vec::new();
+
+    let Some(e) = entities.triship(eid) else {
+        return commands;
+    };
+
+    let centroid = e.body.state.new.shape.centroid();
+    let rotation = e.body.state.new.rotation;
+
+    // run away from anything homing in on us, the hard ones also throw flares
+    let incoming = entities.torpedoes.iter().any(|x| {
+        x.entity.target == Some(eid)
+            && (x.entity.body.state.new.shape.centroid() - centroid).length() < BOT_EVADE_RANGE
+    });
+
+    if incoming && difficulty != Difficulty::Easy {
+        if !e.boost.active && e.boost.cooldown.current == 0 {
+            commands.push(Command::Boost);
+        }
+
+        if difficulty == Difficulty::Hard {
+            commands.push(Command::Countermeasure);
+        }
+    }
+
+    // whoever we've locked on to, otherwise the closest one that isn't on our team
+    let locked = e
+        .targeting
+        .eid
+        .and_then(|x| entities.triship(x).map(|t| (x, t)))
+        .filter(|(_, t)| t.team != e.team);
+
+    let closest = || {
+        entities
+            .triships
+            .iter()
+            .filter(|x| x.id != eid && x.entity.team != e.team)
+            .map(|x| (x.id, &x.entity))
+            .min_by(|(_, a), (_, b)| {
+                let a = (a.body.state.new.shape.centroid() - centroid).length_sqr();
+                let b = (b.body.state.new.shape.centroid() - centroid).length_sqr();
+                a.total_cmp(&b)
+            })
+    };
+
+    let Some((target_id, target)) = locked.or_else(closest) else {
+        return commands;
+    };
+
+    let centroid_target = target.body.state.new.shape.centroid();
+    let distance = (centroid_target - centroid).length();
+
+    // aim where the target will be once our shot gets there, easy bots shoot where it is
+    let aim = match difficulty {
+        Difficulty::Easy => centroid_target,
+        _ => {
+            let time = distance / PROJECTILE_SPEED;
+            centroid_target + (target.motion.velocity - e.motion.velocity) * time
+        }
+    } - centroid;
+
+    let heading = rotation.y.atan2(rotation.x);
+    let error = (aim.y.atan2(aim.x) - heading + PI).rem_euclid(TAU) - PI;
+
+    // turn faster the further off we are, but ease off before overshooting
+    let desired = error * BOT_STEERING;
+    let threshold = e.motion.rotation_acceleration / 2.0;
+
+    if desired > e.motion.rotation_speed + threshold {
+        commands.push(Command::RotateRight);
+    } else if desired < e.motion.rotation_speed - threshold {
+        commands.push(Command::RotateLeft);
+    }
+
+    if distance > BOT_RANGE && error.abs() < PI / 4.0 {
+        commands.push(Command::Accelerate);
+    } else if distance < BOT_RANGE / 2.0 {
+        commands.push(Command::Decelerate);
+    }
+
+    if distance < BOT_FIRE_RANGE && error.abs() < tolerance(difficulty) {
+        commands.push(Command::FirePrimary);
+    }
+
+    if difficulty == Difficulty::Easy {
+        return commands;
+    }
+
+    // torpedoes are for targets out at range, get one ready and lock on first
+    let torpedo = |idx: usize| matches!(e.secondary.weapons[idx].kind, WeaponKind::Torpedo);
+
+    if !(0..e.secondary.weapons.len()).any(torpedo) || distance < BOT_RANGE {
+        return commands;
+    }
+
+    if !torpedo(e.secondary.selected) {
+        commands.push(Command::CycleSecondary);
+    } else if e.targeting.eid != Some(target_id) {
+        // locking cycles through whatever is in reach, the target has to be in there
+        let area = generate_targeting_area(centroid);
+
+        if area.check_collision_recs(&target.body.polygon.bounds_meld.new) {
+            commands.push(Command::TargetLock);
+        }
+    } else if e.targeting.timer.current == 0 && error.abs() < PI / 2.0 {
+        commands.push(Command::FireSecondary);
+    }
+
+    commands
+}
+
+fn held(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Accelerate
+            | Command::Decelerate
+            | Command::RotateLeft
+            | Command::RotateRight
+            | Command::FirePrimary
+    )
+}
+
+// ticks between decisions
+fn reaction(difficulty: Difficulty) -> u8 {
+    match difficulty {
+        Difficulty::Easy => 12,
+        Difficulty::Normal => 6,
+        Difficulty::Hard => 2,
+    }
+}
+
+// how far off, in radians, a shot can be for the bot to still take it
+fn tolerance(difficulty: Difficulty) -> f32 {
+    match difficulty {
+        Difficulty::Easy => 0.3,
+        Difficulty::Normal => 0.15,
+        Difficulty::Hard => 0.06,
+    }
+}
@@ -0,0 +1,77 @@
+use raylib::prelude::*;
+
+use crate::{
+    components::{Acceleratable, Centroidable, Generationable, Renewable},
+    constants::{BOUNDARY_DAMAGE, BOUNDARY_REPEL, BOUNDARY_ZONE},
+    entities::Entities,
+    maps::Map,
+    settings::Boundary,
+};
+
+pub fn update_boundary(entities: &mut Entities, boundary: Boundary, map: &Map) {
+    entities.triships.iter_mut().for_each(|x| {
+        let e = &mut x.entity;
+        let outside = outside(e.body.state.new.shape.centroid(), map);
+
+        if outside == Vector2::zero() {
+            return;
+        }
+
+        match boundary {
+            Boundary::Wrap => {
+                // toroidal space, flying off one edge brings you back on the opposite edge
+                let offset = Vector2::new(
+                    wrap(outside.x, map.width as f32),
+                    wrap(outside.y, map.height as f32),
+                );
+
+                e.body.accelerate(offset);
+                e.body.renew();
+
+                // teleport, we don't want to interpolate the triship across the cosmos
+                e.body.generation();
+            }
+            Boundary::Damage => {
+                e.life -= BOUNDARY_DAMAGE;
+            }
+            Boundary::Repel => {
+                // the further out, the harder we push back, up until the end of the zone
+                let depth = outside.length();
+                let force = (depth / BOUNDARY_ZONE).min(1.0) * BOUNDARY_REPEL;
+
+                e.motion.velocity -= outside.normalized() * force;
+            }
+        }
+    });
+}
+
+fn outside(position: Vector2, map: &Map) -> Vector2 {
+    // how far outside of the cosmos the position is, zero when within
+    let x = if position.x < 0.0 {
+        position.x
+    } else if position.x > map.width as f32 {
+        position.x - map.width as f32
+    } else {
+        0.0
+    };
+
+    let y = if position.y < 0.0 {
+        position.y
+    } else if position.y > map.height as f32 {
+        position.y - map.height as f32
+    } else {
+        0.0
+    };
+
+    Vector2::new(x, y)
+}
+
+fn wrap(outside: f32, size: f32) -> f32 {
+    if outside < 0.0 {
+        size
+    } else if outside > 0.0 {
+        -size
+    } else {
+        0.0
+    }
+}
@@ -1,33 +1,67 @@
 use raylib::prelude::*;
 
 use crate::{
-    components::{Boundable, Centroidable, Generation, Shape},
-    entities::{Entities, Entity, EntityIndex},
+    components::{Centroidable, Generation, Renewable, Response, Rotatable, Shape},
+    constants::{ASTEROID_BOUNCE, ASTEROID_RADIUS_MIN, COLLISION_STEPS_MAX, RICOCHET_IMPACT},
+    entities::{Archetype, Entities, Entity, EntityIndex, Projectile},
     forge::Forge,
-    quadtree::{Node, NodeType, QuadTree},
+    quadtree::QuadTree,
+    random::Random,
+};
+
+use super::{
+    objectives::{encumber, flag_home, return_flag, unencumber},
+    pickups::collect,
+    shields::damage_triship,
 };
 
 pub fn update_collision_reaction(
     entities: &mut Entities,
-    collisions: &mut Vec<(EntityIndex, EntityIndex)>,
+    collisions: &mut Vec<(EntityIndex, EntityIndex, Contact)>,
     forge: &Forge,
-    h: &mut RaylibHandle,
+    h: &mut dyn Random,
 ) {
-    while let Some((eidx1, eidx2)) = collisions.pop() {
-        match (eidx1, eidx2) {
-            (EntityIndex::Triship(idx1), EntityIndex::Triship(idx2)) => {
-                handle_triship_triship(idx1, idx2, entities, forge, h)
+    while let Some((eidx1, eidx2, contact)) = collisions.pop() {
+        // the normal points from the first entity towards the second one
+        let normal = contact.normal;
+        let facing = |eidx: EntityIndex| if eidx == eidx1 { normal } else { normal * -1.0 };
+
+        // whatever kills or bounces doesn't care what ran into it
+        let response1 = entities.archetype(eidx1).layers().response;
+        let response2 = entities.archetype(eidx2).layers().response;
+        let other = |response: Response| {
+            if response1 == response {
+                Some(eidx2)
+            } else if response2 == response {
+                Some(eidx1)
+            } else {
+                None
             }
+        };
+
+        if let Some(other) = other(Response::Kill) {
+            handle_kill(other, entities, forge, h);
+            continue;
+        }
+
+        if let Some(other) = other(Response::Bounce) {
+            handle_bounce(other, facing(other), entities, forge, h);
+            continue;
+        }
+
+        match (eidx1, eidx2) {
             (EntityIndex::Triship(idx_tri), EntityIndex::Projectile(idx_pro))
             | (EntityIndex::Projectile(idx_pro), EntityIndex::Triship(idx_tri)) => {
-                handle_triship_projectile(idx_tri, idx_pro, entities, forge, h)
+                let normal = facing(EntityIndex::Triship(idx_tri));
+                handle_triship_projectile(idx_tri, idx_pro, normal, entities, forge, h)
             }
             (EntityIndex::Projectile(idx1), EntityIndex::Projectile(idx2)) => {
                 handle_projectile_projectile(idx1, idx2, entities, forge, h)
             }
             (EntityIndex::Torpedo(idx_tor), EntityIndex::Triship(idx_tri))
             | (EntityIndex::Triship(idx_tri), EntityIndex::Torpedo(idx_tor)) => {
-                handle_triship_torpedo(idx_tri, idx_tor, entities, forge, h)
+                let normal = facing(EntityIndex::Triship(idx_tri));
+                handle_triship_torpedo(idx_tri, idx_tor, normal, entities, forge, h)
             }
             (EntityIndex::Torpedo(idx_tor), EntityIndex::Projectile(idx_pro))
             | (EntityIndex::Projectile(idx_pro), EntityIndex::Torpedo(idx_tor)) => {
@@ -36,16 +70,386 @@ pub fn update_collision_reaction(
             (EntityIndex::Torpedo(idx1), EntityIndex::Torpedo(idx2)) => {
                 handle_torpedo_torpedo(idx1, idx2, entities, forge, h)
             }
+            (EntityIndex::Triship(idx_tri), EntityIndex::Flag(idx_fla))
+            | (EntityIndex::Flag(idx_fla), EntityIndex::Triship(idx_tri)) => {
+                handle_triship_flag(idx_tri, idx_fla, entities)
+            }
+            (EntityIndex::Triship(idx_tri), EntityIndex::Base(idx_bas))
+            | (EntityIndex::Base(idx_bas), EntityIndex::Triship(idx_tri)) => {
+                handle_triship_base(idx_tri, idx_bas, entities)
+            }
+            (EntityIndex::Triship(idx_tri), EntityIndex::Asteroid(idx_ast))
+            | (EntityIndex::Asteroid(idx_ast), EntityIndex::Triship(idx_tri)) => {
+                let normal = facing(EntityIndex::Triship(idx_tri));
+                handle_triship_asteroid(idx_tri, idx_ast, normal, entities, forge, h)
+            }
+            (EntityIndex::Projectile(idx_pro), EntityIndex::Asteroid(idx_ast))
+            | (EntityIndex::Asteroid(idx_ast), EntityIndex::Projectile(idx_pro)) => {
+                let normal = facing(EntityIndex::Projectile(idx_pro));
+                handle_projectile_asteroid(idx_pro, idx_ast, normal, entities, forge, h)
+            }
+            (EntityIndex::Torpedo(idx_tor), EntityIndex::Asteroid(idx_ast))
+            | (EntityIndex::Asteroid(idx_ast), EntityIndex::Torpedo(idx_tor)) => {
+                handle_torpedo_asteroid(idx_tor, idx_ast, entities, forge, h)
+            }
+            (EntityIndex::Triship(idx_tri), EntityIndex::Pickup(idx_pic))
+            | (EntityIndex::Pickup(idx_pic), EntityIndex::Triship(idx_tri)) => {
+                handle_triship_pickup(idx_tri, idx_pic, entities)
+            }
+            (EntityIndex::Triship(idx_tri), EntityIndex::Mine(idx_min))
+            | (EntityIndex::Mine(idx_min), EntityIndex::Triship(idx_tri)) => {
+                let normal = facing(EntityIndex::Triship(idx_tri));
+                handle_triship_mine(idx_tri, idx_min, normal, entities, forge, h)
+            }
+            (EntityIndex::Projectile(idx_pro), EntityIndex::Mine(idx_min))
+            | (EntityIndex::Mine(idx_min), EntityIndex::Projectile(idx_pro)) => {
+                handle_projectile_mine(idx_pro, idx_min, entities, forge, h)
+            }
+            (EntityIndex::Torpedo(idx_tor), EntityIndex::Mine(idx_min))
+            | (EntityIndex::Mine(idx_min), EntityIndex::Torpedo(idx_tor)) => {
+                handle_torpedo_mine(idx_tor, idx_min, entities, forge, h)
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_bounce(
+        eidx: EntityIndex,
+        normal: Vector2,
+        entities: &mut Entities,
+        forge: &Forge,
+        h: &mut dyn Random,
+    ) {
+        // mirror the velocity along the wall and lose some of it on impact
+        let bounce = |velocity: Vector2| {
+            let dot = velocity.dot(normal);
+            if dot <= 0.0 {
+                return velocity;
+            }
+
+            (velocity - normal * 2.0 * dot) / 2.0 - normal * ASTEROID_BOUNCE
+        };
+
+        match eidx {
+            EntityIndex::Triship(idx) => {
+                let t = &mut entities.triships[idx].entity;
+                t.motion.velocity = bounce(t.motion.velocity);
+            }
+            EntityIndex::Asteroid(idx) => {
+                let a = &mut entities.asteroids[idx].entity;
+                a.motion.velocity = bounce(a.motion.velocity);
+            }
+            EntityIndex::Projectile(idx) => {
+                let p = &mut entities.projectiles[idx].entity;
+                if ricochet(p, normal) {
+                    return;
+                }
+
+                p.life = 0.0;
+
+                // spawn explosion!
+                for explosion in forge.explosion_projectile(p.body.polygon.vertexes.new[1], h) {
+                    entities.add(Entity::Explosion(explosion));
+                }
+            }
+            EntityIndex::Torpedo(idx) => {
+                let t = &mut entities.torpedoes[idx].entity;
+                if t.life <= 0.0 {
+                    return;
+                }
+
+                t.life = 0.0;
+
+                let c = t.body.state.new.shape.centroid();
+                for explosion in forge.explosion_torpedo(c, h) {
+                    entities.add(Entity::Explosion(explosion));
+                }
+            }
             _ => (),
         }
     }
 
+    fn handle_kill(eidx: EntityIndex, entities: &mut Entities, forge: &Forge, h: &mut dyn Random) {
+        // nothing survives the core
+        match eidx {
+            EntityIndex::Triship(idx) => {
+                let t = &mut entities.triships[idx].entity;
+
+                // already dead this tick
+                if t.life <= 0.0 {
+                    return;
+                }
+
+                t.life = 0.0;
+                explode_triship_if_dead(idx, forge, entities, h);
+            }
+            EntityIndex::Projectile(idx) => {
+                entities.projectiles[idx].entity.life = 0.0;
+            }
+            EntityIndex::Torpedo(idx) => {
+                let t = &mut entities.torpedoes[idx].entity;
+                if t.life <= 0.0 {
+                    return;
+                }
+
+                t.life = 0.0;
+
+                let c = t.body.state.new.shape.centroid();
+                for explosion in forge.explosion_torpedo(c, h) {
+                    entities.add(Entity::Explosion(explosion));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_triship_mine(
+        idx_tri: usize,
+        idx_min: usize,
+        normal: Vector2,
+        entities: &mut Entities,
+        forge: &Forge,
+        h: &mut dyn Random,
+    ) {
+        // mine has already gone off this tick
+        if entities.mines[idx_min].entity.life <= 0.0 {
+            return;
+        }
+
+        let tri = &mut entities.triships[idx_tri];
+        let min = &mut entities.mines[idx_min];
+
+        damage_triship(
+            &mut tri.entity,
+            min.entity.damage,
+            normal,
+            Some(min.entity.owner_id),
+        );
+        min.entity.life = 0.0;
+
+        let c = min.entity.body.state.new.shape.centroid();
+
+        // spawn explosion!
+        for explosion in forge.explosion_torpedo(c, h) {
+            entities.add(Entity::Explosion(explosion));
+        }
+
+        explode_triship_if_dead(idx_tri, forge, entities, h);
+    }
+
+    fn handle_projectile_mine(
+        idx_pro: usize,
+        idx_min: usize,
+        entities: &mut Entities,
+        forge: &Forge,
+        h: &mut dyn Random,
+    ) {
+        // mine has already gone off this tick
+        if entities.mines[idx_min].entity.life <= 0.0 {
+            return;
+        }
+
+        let p = &mut entities.projectiles[idx_pro];
+        let m = &mut entities.mines[idx_min];
+
+        p.entity.life = 0.0;
+        m.entity.life = 0.0;
+
+        let c = m.entity.body.state.new.shape.centroid();
+
+        // spawn explosion!
+        for explosion in forge.explosion_torpedo(c, h) {
+            entities.add(Entity::Explosion(explosion));
+        }
+    }
+
+    fn handle_torpedo_mine(
+        idx_tor: usize,
+        idx_min: usize,
+        entities: &mut Entities,
+        forge: &Forge,
+        h: &mut dyn Random,
+    ) {
+        // mine has already gone off this tick
+        if entities.mines[idx_min].entity.life <= 0.0 {
+            return;
+        }
+
+        let t = &mut entities.torpedoes[idx_tor];
+        let m = &mut entities.mines[idx_min];
+
+        t.entity.life = 0.0;
+        m.entity.life = 0.0;
+
+        let c = m.entity.body.state.new.shape.centroid();
+
+        // spawn explosion!
+        for explosion in forge.explosion_torpedo(c, h) {
+            entities.add(Entity::Explosion(explosion));
+        }
+    }
+
+    fn handle_triship_pickup(idx_tri: usize, idx_pic: usize, entities: &mut Entities) {
+        let pic = &mut entities.pickups[idx_pic].entity;
+
+        // someone else got to it first this tick
+        if pic.respawn.current != 0 {
+            return;
+        }
+
+        collect(&mut entities.triships[idx_tri].entity, pic);
+    }
+
+    fn handle_triship_asteroid(
+        idx_tri: usize,
+        idx_ast: usize,
+        normal: Vector2,
+        entities: &mut Entities,
+        forge: &Forge,
+        h: &mut dyn Random,
+    ) {
+        let ast = &entities.asteroids[idx_ast].entity;
+        let c_ast = ast.body.state.new.shape.centroid();
+        let damage = ast.damage;
+
+        let tri = &mut entities.triships[idx_tri].entity;
+        let c_tri = tri.body.state.new.shape.centroid();
+
+        damage_triship(tri, damage, normal, None);
+
+        // push the triship away from the asteroid, otherwise we keep on grinding against it
+        let away = (c_tri - c_ast).normalized();
+        tri.motion.velocity = away * (tri.motion.velocity.length() / 2.0 + ASTEROID_BOUNCE);
+
+        // spawn explosion!
+        for explosion in forge.explosion_projectile(c_tri, h) {
+            entities.add(Entity::Explosion(explosion));
+        }
+
+        explode_triship_if_dead(idx_tri, forge, entities, h);
+    }
+
+    fn handle_projectile_asteroid(
+        idx_pro: usize,
+        idx_ast: usize,
+        normal: Vector2,
+        entities: &mut Entities,
+        forge: &Forge,
+        h: &mut dyn Random,
+    ) {
+        // asteroid has already been split this tick
+        if entities.asteroids[idx_ast].entity.life <= 0.0 {
+            return;
+        }
+
+        let p = &mut entities.projectiles[idx_pro];
+        let a = &mut entities.asteroids[idx_ast];
+
+        // glances off the rock without chipping it
+        if ricochet(&mut p.entity, normal) {
+            return;
+        }
+
+        a.entity.life -= p.entity.damage;
+        p.entity.life = 0.0;
+
+        // spawn explosion!
+        for explosion in forge.explosion_projectile(p.entity.body.polygon.vertexes.new[1], h) {
+            entities.add(Entity::Explosion(explosion));
+        }
+
+        split_asteroid_if_dead(idx_ast, forge, entities, h);
+    }
+
+    fn handle_torpedo_asteroid(
+        idx_tor: usize,
+        idx_ast: usize,
+        entities: &mut Entities,
+        forge: &Forge,
+        h: &mut dyn Random,
+    ) {
+        // asteroid has already been split this tick
+        if entities.asteroids[idx_ast].entity.life <= 0.0 {
+            return;
+        }
+
+        let t = &mut entities.torpedoes[idx_tor];
+        let a = &mut entities.asteroids[idx_ast];
+
+        a.entity.life -= t.entity.damage;
+        t.entity.life = 0.0;
+
+        let c = t.entity.body.state.new.shape.centroid();
+
+        // spawn explosion!
+        for explosion in forge.explosion_torpedo(c, h) {
+            entities.add(Entity::Explosion(explosion));
+        }
+
+        split_asteroid_if_dead(idx_ast, forge, entities, h);
+    }
+
+    fn handle_triship_flag(idx_tri: usize, idx_fla: usize, entities: &mut Entities) {
+        let tri = &mut entities.triships[idx_tri];
+        let fla = &mut entities.flags[idx_fla].entity;
+
+        // someone is already carrying it
+        if fla.carrier.is_some() {
+            return;
+        }
+
+        if fla.team == tri.entity.team {
+            // touching our own dropped flag returns it
+            if !flag_home(fla) {
+                return_flag(fla);
+            }
+
+            return;
+        }
+
+        fla.carrier = Some(tri.id);
+
+        encumber(&mut tri.entity);
+    }
+
+    fn handle_triship_base(idx_tri: usize, idx_bas: usize, entities: &mut Entities) {
+        let tri = &entities.triships[idx_tri];
+        let team = entities.bases[idx_bas].entity.team;
+
+        if tri.entity.team != team {
+            return;
+        }
+
+        let Some(idx_fla) = entities
+            .flags
+            .iter()
+            .position(|x| x.entity.carrier == Some(tri.id))
+        else {
+            return;
+        };
+
+        // we can only score while our own flag is safe at home
+        if !entities
+            .flags
+            .iter()
+            .filter(|x| x.entity.team == team)
+            .all(|x| flag_home(&x.entity))
+        {
+            return;
+        }
+
+        entities.bases[idx_bas].entity.score += 1;
+
+        return_flag(&mut entities.flags[idx_fla].entity);
+        unencumber(&mut entities.triships[idx_tri].entity);
+    }
+
     fn handle_torpedo_torpedo(
         idx1: usize,
         idx2: usize,
         entities: &mut Entities,
         forge: &Forge,
-        h: &mut RaylibHandle,
+        h: &mut dyn Random,
     ) {
         for t in [idx1, idx2] {
             let t = &mut entities.torpedoes[t];
@@ -66,7 +470,7 @@ pub fn update_collision_reaction(
         idx_t: usize,
         entities: &mut Entities,
         forge: &Forge,
-        h: &mut RaylibHandle,
+        h: &mut dyn Random,
     ) {
         let p = &mut entities.projectiles[idx_p];
         let t = &mut entities.torpedoes[idx_t];
@@ -85,14 +489,20 @@ pub fn update_collision_reaction(
     fn handle_triship_torpedo(
         idx_tri: usize,
         idx_tor: usize,
+        normal: Vector2,
         entities: &mut Entities,
         forge: &Forge,
-        h: &mut RaylibHandle,
+        h: &mut dyn Random,
     ) {
         let tri = &mut entities.triships[idx_tri];
         let tor = &mut entities.torpedoes[idx_tor];
 
-        tri.entity.life -= tor.entity.damage;
+        damage_triship(
+            &mut tri.entity,
+            tor.entity.damage,
+            normal,
+            Some(tor.entity.owner_id),
+        );
         tor.entity.life = 0.0;
 
         let c = tor.entity.body.state.new.shape.centroid();
@@ -108,14 +518,20 @@ pub fn update_collision_reaction(
     fn handle_triship_projectile(
         idx_t: usize,
         idx_p: usize,
+        normal: Vector2,
         entities: &mut Entities,
         forge: &Forge,
-        h: &mut RaylibHandle,
+        h: &mut dyn Random,
     ) {
         let t = &mut entities.triships[idx_t];
         let p = &mut entities.projectiles[idx_p];
 
-        t.entity.life -= p.entity.damage;
+        damage_triship(
+            &mut t.entity,
+            p.entity.damage,
+            normal,
+            Some(p.entity.owner_id),
+        );
         p.entity.life = 0.0;
 
         // spawn explosion!
@@ -126,22 +542,12 @@ pub fn update_collision_reaction(
         explode_triship_if_dead(idx_t, forge, entities, h);
     }
 
-    fn handle_triship_triship(
-        _idx1: usize,
-        _idx2: usize,
-        _entities: &mut Entities,
-        _forge: &Forge,
-        _h: &mut RaylibHandle,
-    ) {
-        // TODO: boom?
-    }
-
     fn handle_projectile_projectile(
         idx1: usize,
         idx2: usize,
         entities: &mut Entities,
         forge: &Forge,
-        h: &mut RaylibHandle,
+        h: &mut dyn Random,
     ) {
         let p2 = &mut entities.projectiles[idx2];
         p2.entity.life = 0.0;
@@ -154,276 +560,275 @@ pub fn update_collision_reaction(
             entities.add(Entity::Explosion(explosion));
         }
     }
+}
 
-    fn explode_triship_if_dead(
-        idx: usize,
-        forge: &Forge,
-        entities: &mut Entities,
-        h: &mut RaylibHandle,
-    ) {
-        let t = &entities.triships[idx];
-        if t.entity.life > 0.0 {
-            return;
-        }
+pub fn split_asteroid_if_dead(
+    idx: usize,
+    forge: &Forge,
+    entities: &mut Entities,
+    h: &mut dyn Random,
+) {
+    let a = &entities.asteroids[idx].entity;
+    if a.life > 0.0 {
+        return;
+    }
 
-        let c = t.entity.body.state.new.shape.centroid();
+    let c = a.body.state.new.shape.centroid();
 
-        for explosion in forge.explosion_triship(c, h) {
-            entities.add(Entity::Explosion(explosion));
-        }
+    // the smallest rocks just crumble
+    let fragments = if a.radius / 2.0 < ASTEROID_RADIUS_MIN {
+        Vec::new()
+    } else {
+        forge.asteroid_fragments(a, h)
+    };
+
+    for fragment in fragments {
+        entities.add(Entity::Asteroid(fragment));
+    }
+
+    for explosion in forge.explosion_triship(c, h) {
+        entities.add(Entity::Explosion(explosion));
+    }
+}
+
+// projectiles coming in flat enough glance off, the normal points into whatever they hit,
+// returns false if it should go off instead
+fn ricochet(p: &mut Projectile, normal: Vector2) -> bool {
+    let velocity = p.motion.velocity;
+    let speed = velocity.length();
+    if speed == 0.0 {
+        return false;
+    }
+
+    // the angle between the way it's heading and the surface
+    let impact = velocity.dot(normal) / speed;
+    if impact > RICOCHET_IMPACT {
+        return false;
+    }
+
+    // already on its way out, nothing left to do
+    if impact <= 0.0 {
+        return true;
+    }
+
+    let reflected = velocity - normal * 2.0 * velocity.dot(normal);
+    let rotation = p.body.state.new.rotation;
+
+    p.motion.velocity = reflected;
+    p.body
+        .rotate(reflected.y.atan2(reflected.x) - rotation.y.atan2(rotation.x));
+    p.body.renew();
+
+    true
+}
+
+pub fn explode_triship_if_dead(
+    idx: usize,
+    forge: &Forge,
+    entities: &mut Entities,
+    h: &mut dyn Random,
+) {
+    let t = &entities.triships[idx];
+    if t.entity.life > 0.0 {
+        return;
+    }
+
+    let c = t.entity.body.state.new.shape.centroid();
+
+    for explosion in forge.explosion_triship(c, h) {
+        entities.add(Entity::Explosion(explosion));
     }
 }
 
 pub fn update_collision_detection(
     entities: &mut Entities,
     quadtree: &mut QuadTree,
-    collisions: &mut Vec<(EntityIndex, EntityIndex)>,
+    collisions: &mut Vec<(EntityIndex, EntityIndex, Contact)>,
 ) {
-    quadtree.reset();
-
-    entities
-        .triships
-        .iter()
-        .map(|x| x.id)
-        .chain(entities.projectiles.iter().map(|x| x.id))
-        .chain(entities.torpedoes.iter().map(|x| x.id))
-        .for_each(|eid| {
-            quadtree.add(eid, &entities);
-        });
-
-    run(&quadtree.root, entities, collisions);
-
-    fn run(node: &Node, entities: &mut Entities, collisions: &mut Vec<(EntityIndex, EntityIndex)>) {
-        match &node.node_type {
-            NodeType::Leaf(ents) => {
-                for i in 0..ents.len() {
-                    let eidx1 = ents[i];
-                    let bounds1 = bounds(eidx1, entities);
-
-                    for j in i + 1..ents.len() {
-                        let eidx2 = ents[j];
-
-                        match (eidx1, eidx2) {
-                            // let's not shoot projectiles with projectiles for now, it's extremely cpu intensive...
-                            (EntityIndex::Projectile(_), EntityIndex::Projectile(_)) => {
-                                continue;
-                            }
-                            _ => (),
-                        }
-
-                        let bounds2 = bounds(eidx2, entities);
-
-                        // perform initial collision check using the meld bounds,
-                        // if there's a hit, we perform a more thorough collision check
-                        // using the SAT (separating axis theorem) while incrementally moving
-                        // the entities from the old to the new location at most 1.0 distance at a time
-                        // TODO: there's probably a more elegant way to solve this, but me grug brain
-                        if !bounds1.check_collision_recs(&bounds2) {
-                            continue;
-                        }
-
-                        // TODO: probably better to calculate all the stuff for the first entity outside this loop
-                        let vert1 = vertexes(eidx1, entities);
-                        let vert2 = vertexes(eidx2, entities);
-
-                        let vel1 = vert1.new[0] - vert1.old[0];
-                        let vel2 = vert2.new[0] - vert2.old[0];
-
-                        let dir1 = vel1.normalized();
-                        let dir2 = vel2.normalized();
-
-                        let speed_max1 = vel1.length();
-                        let speed_max2 = vel2.length();
-
-                        // we want to keep the increments at 1.0 or less
-                        let mut speed_incr1 = speed_max1;
-                        let mut speed_incr2 = speed_max2;
-                        while speed_incr1 > 1.0 || speed_incr2 > 1.0 {
-                            speed_incr1 /= 2.0;
-                            speed_incr2 /= 2.0;
-                        }
-
-                        let vert_old1 = &vert1.old;
-                        let vert_old2 = &vert2.old;
-
-                        let mut speed_cur1 = 0.0;
-                        let mut speed_cur2 = 0.0;
-
-                        // TODO: check if objects are moving away from each other,
-                        // if so, can we guarantee that there won't be a collision and early exit..?
-
-                        loop {
-                            speed_cur1 += speed_incr1;
-                            speed_cur2 += speed_incr2;
-
-                            // no movement, should probably already have collided
-                            if speed_cur1 == 0.0 && speed_cur2 == 0.0 {
-                                break;
-                            }
-
-                            // no collision!
-                            if speed_cur1 > speed_max1 || speed_cur2 > speed_max2 {
-                                break;
-                            }
-
-                            // keep moving the vertexes incrementally until we find the first collision
-                            let vert_cur1 = vert_old1
-                                .iter()
-                                .map(|x| *x + dir1 * speed_cur1)
-                                .collect::<Vec<_>>();
-
-                            let vert_cur2 = vert_old2
-                                .iter()
-                                .map(|x| *x + dir2 * speed_cur2)
-                                .collect::<Vec<_>>();
-
-                            let mut overlap = f32::MAX;
-                            let mut smallest = Vector2::zero();
-
-                            let axes1 = axes(&vert_cur1);
-                            if !overlapping(
-                                &vert_cur1,
-                                &vert_cur2,
-                                &axes1,
-                                &mut overlap,
-                                &mut smallest,
-                            ) {
-                                continue;
-                            }
-
-                            let axes2 = axes(&vert_cur2);
-                            if !overlapping(
-                                &vert_cur1,
-                                &vert_cur2,
-                                &axes2,
-                                &mut overlap,
-                                &mut smallest,
-                            ) {
-                                continue;
-                            }
-
-                            match (eidx1, eidx2) {
-                                (EntityIndex::Triship(_), EntityIndex::Triship(_)) => {
-                                    break;
-
-                                    // reposition(
-                                    //     eidx1,
-                                    //     dir1 * -1.0 * speed_max1 + dir1 * speed_cur1,
-                                    //     entities,
-                                    // );
-
-                                    // reposition(
-                                    //     eidx2,
-                                    //     dir2 * -1.0 * speed_max2 + dir2 * speed_cur2,
-                                    //     entities,
-                                    // );
-                                }
-                                (
-                                    EntityIndex::Triship(_),
-                                    EntityIndex::Projectile(_) | EntityIndex::Torpedo(_),
-                                ) => {
-                                    reposition(
-                                        eidx2,
-                                        dir2 * -1.0 * speed_max2 + dir2 * speed_cur2,
-                                        entities,
-                                    );
-                                }
-                                (
-                                    EntityIndex::Projectile(_) | EntityIndex::Torpedo(_),
-                                    EntityIndex::Triship(_),
-                                ) => {
-                                    reposition(
-                                        eidx1,
-                                        dir1 * -1.0 * speed_max1 + dir1 * speed_cur1,
-                                        entities,
-                                    );
-                                }
-                                (EntityIndex::Torpedo(_), EntityIndex::Torpedo(_)) => {
-                                    reposition(
-                                        eidx1,
-                                        dir1 * -1.0 * speed_max1 + dir1 * speed_cur1,
-                                        entities,
-                                    );
-
-                                    reposition(
-                                        eidx2,
-                                        dir2 * -1.0 * speed_max2 + dir2 * speed_cur2,
-                                        entities,
-                                    );
-                                }
-                                _ => (),
-                            }
-
-                            collisions.push((eidx1, eidx2));
-
-                            break;
-                        }
-                    }
-                }
+    // whatever doesn't want to hit anything right now can stay out of the index
+    let wants = |x: &dyn Archetype| x.collider().is_some() && x.layers().mask != 0;
+
+    // the index is kept from the last tick, the dead have to go and everyone else
+    // only moves if they left their node
+    quadtree.retain(|eid| {
+        entities
+            .entity(eid)
+            .is_some_and(|eidx| wants(entities.archetype(eidx)))
+    });
+
+    entities.each(|eid, x| {
+        if wants(x) {
+            quadtree.add(eid, entities);
+        }
+    });
+
+    // where the vertexes are in between generations, shared by every pair
+    let mut scratch = (Vec::new(), Vec::new());
+
+    // the index only hands out pairs whose meld bounds overlap
+    quadtree.pairs(|eidx1, eidx2| detect(eidx1, eidx2, entities, collisions, &mut scratch));
+
+    // the reactions must happen in the same order everywhere no matter how the index was built
+    collisions.sort_by_key(|(eidx1, eidx2, _)| (*eidx1, *eidx2));
+
+    fn detect(
+        eidx1: EntityIndex,
+        eidx2: EntityIndex,
+        entities: &mut Entities,
+        collisions: &mut Vec<(EntityIndex, EntityIndex, Contact)>,
+        (vert_cur1, vert_cur2): &mut (Vec<Vector2>, Vec<Vector2>),
+    ) {
+        // both of them have to be on a layer the other one wants to hit
+        let layers1 = entities.archetype(eidx1).layers();
+        let layers2 = entities.archetype(eidx2).layers();
+
+        if !layers1.hits(&layers2) {
+            return;
+        }
+
+        // the meld bounds already overlap, so perform a more thorough collision check
+        // using the SAT (separating axis theorem) while sweeping the entities
+        // from their old to their new vertexes
+        let (Some(vert1), Some(vert2)) = (vertexes(eidx1, entities), vertexes(eidx2, entities))
+        else {
+            return;
+        };
+
+        let vel1 = vert1.new[0] - vert1.old[0];
+        let vel2 = vert2.new[0] - vert2.old[0];
+
+        // no point of either of them moves further than this relative to the other one
+        let speed = sweep(vert1) + sweep(vert2);
+
+        lerp(vert1, 0.0, vert_cur1);
+        lerp(vert2, 0.0, vert_cur2);
+
+        let mut gap = separation(vert_cur1, vert_cur2);
+        let mut t = 0.0;
+
+        for i in 1..=COLLISION_STEPS_MAX {
+            if t >= 1.0 {
+                break;
             }
-            NodeType::Branch(nodes) => {
-                for node in nodes {
-                    run(&node, entities, collisions);
-                }
+
+            // conservative advancement, as long as they're apart they can close the gap
+            // but never skip past each other, at least 1.0 at a time so grazes end too,
+            // a body that wrapped around would go on forever so the last step lands on new
+            t = if speed > 0.0 && i < COLLISION_STEPS_MAX {
+                (t + gap.max(1.0) / speed).min(1.0)
+            } else {
+                1.0
+            };
+
+            lerp(vert1, t, vert_cur1);
+            lerp(vert2, t, vert_cur2);
+
+            let Some(contact) = contact(vert_cur1, vert_cur2) else {
+                gap = separation(vert_cur1, vert_cur2);
+                continue;
+            };
+
+            // move back from the new location to where they first touched,
+            // whoever gives way is pushed apart along the contact as well
+            let mtv = contact.mtv();
+
+            if let Some(share) = share(layers1.response, layers2.response) {
+                reposition(eidx1, vel1 * (t - 1.0) - mtv * share, entities);
+            }
+
+            if let Some(share) = share(layers2.response, layers1.response) {
+                reposition(eidx2, vel2 * (t - 1.0) + mtv * share, entities);
             }
+
+            collisions.push((eidx1, eidx2, contact));
+
+            break;
         }
     }
 
+    // how much of the contact a body gives way, none if it stays right where it is,
+    // sensors only want to know about the overlap so nobody gets pushed around
+    fn share(response: Response, other: Response) -> Option<f32> {
+        match (response, other) {
+            (_, Response::Sensor | Response::Kill) => None,
+            (Response::Stop, _) => Some(0.0),
+            (Response::Push, Response::Push) => Some(0.5),
+            (Response::Push, Response::Bounce) => Some(1.0),
+            _ => None,
+        }
+    }
+
+    fn sweep(vertexes: &Generation<Vec<Vector2>>) -> f32 {
+        // rotation moves some vertexes further than others
+        vertexes
+            .old
+            .iter()
+            .zip(vertexes.new.iter())
+            .map(|(old, new)| (*new - *old).length())
+            .fold(0.0, f32::max)
+    }
+
+    fn lerp(vertexes: &Generation<Vec<Vector2>>, amount: f32, into: &mut Vec<Vector2>) {
+        into.clear();
+        into.extend(
+            vertexes
+                .old
+                .iter()
+                .zip(vertexes.new.iter())
+                .map(|(old, new)| old.lerp(*new, amount)),
+        );
+    }
+
     fn reposition(eidx: EntityIndex, vel: Vector2, entities: &mut Entities) {
-        let s = shape(eidx, entities);
+        // obstacles and such don't have a body to move
+        let Some(s) = shape(eidx, entities) else {
+            return;
+        };
+
         s.accelerate(vel);
         s.renew();
     }
 
-    fn vertexes(eidx: EntityIndex, entities: &Entities) -> &Generation<Vec<Vector2>> {
-        match eidx {
-            EntityIndex::Triship(idx) => &entities.triships[idx].entity.body.polygon.vertexes,
-            EntityIndex::Projectile(idx) => &entities.projectiles[idx].entity.body.polygon.vertexes,
-            EntityIndex::Torpedo(idx) => &entities.torpedoes[idx].entity.body.polygon.vertexes,
-            _ => panic!("vertexes {:?}", eidx),
-        }
+    fn vertexes(eidx: EntityIndex, entities: &Entities) -> Option<&Generation<Vec<Vector2>>> {
+        entities.archetype(eidx).collider().map(|x| &x.vertexes)
     }
 
-    fn bounds(eidx: EntityIndex, entities: &Entities) -> Rectangle {
-        match eidx {
-            EntityIndex::Triship(idx) => &entities.triships[idx].entity.body.polygon,
-            EntityIndex::Projectile(idx) => &entities.projectiles[idx].entity.body.polygon,
-            EntityIndex::Torpedo(idx) => &entities.torpedoes[idx].entity.body.polygon,
-            _ => panic!("bounds {:?}", eidx),
-        }
-        .bounds_meld
-        .bounds()
+    fn shape(eidx: EntityIndex, entities: &mut Entities) -> Option<&mut dyn Shape> {
+        entities.parts(eidx).body
     }
+}
 
-    fn shape(eidx: EntityIndex, entities: &mut Entities) -> &mut dyn Shape {
-        match eidx {
-            EntityIndex::Triship(idx) => &mut entities.triships[idx].entity.body as &mut dyn Shape,
-            EntityIndex::Projectile(idx) => {
-                &mut entities.projectiles[idx].entity.body as &mut dyn Shape
-            }
-            EntityIndex::Torpedo(idx) => &mut entities.torpedoes[idx].entity.body as &mut dyn Shape,
-            _ => panic!("shape {:?}", eidx),
-        }
+// how two polygons overlap, the normal points from the first one towards the second one
+#[derive(Clone, Copy, Debug)]
+pub struct Contact {
+    pub normal: Vector2,
+    pub depth: f32,
+}
+
+impl Contact {
+    // the minimal translation vector, moving the second polygon by it separates them
+    pub fn mtv(&self) -> Vector2 {
+        self.normal * self.depth
     }
 }
 
-pub fn overlapping(
-    v_one: &[Vector2],
-    v_two: &[Vector2],
-    axes: &[Vector2],
-    overlap: &mut f32,
-    smallest: &mut Vector2,
-) -> bool {
-    for axis in axes {
-        let p_one = project(v_one, *axis);
-        let p_two = project(v_two, *axis);
+// SAT (separating axis theorem) for convex polygons with any number of vertexes
+pub fn contact(v_one: &[Vector2], v_two: &[Vector2]) -> Option<Contact> {
+    let mut depth = f32::MAX;
+    let mut normal = Vector2::zero();
 
+    for axis in axes(v_one).chain(axes(v_two)) {
+        let p_one = project(v_one, axis);
+        let p_two = project(v_two, axis);
+
+        // found a gap, nothing overlaps
         if !(p_one.y > p_two.x || p_one.x > p_two.y) {
-            return false;
+            return None;
         }
 
         let mut o = p_one.y.min(p_two.y) - p_one.x.max(p_two.x);
 
+        // one is within the other on this axis, it has to get out on the closer side
         if contains(p_one, p_two) || contains(p_two, p_one) {
             let min = (p_one.x - p_two.x).abs();
             let max = (p_one.y - p_two.y).abs();
@@ -435,30 +840,54 @@ pub fn overlapping(
             }
         }
 
-        // if we don't want the mtv(minimal translation vector), we can remove overlap and smallest
-        if o < *overlap {
-            *overlap = o;
-            *smallest = *axis;
+        if o < depth {
+            depth = o;
+            normal = axis;
         }
     }
 
-    true
+    // no axes at all, points and other degenerate polygons can't overlap anything
+    if depth == f32::MAX {
+        return None;
+    }
+
+    if normal.dot(center(v_two) - center(v_one)) < 0.0 {
+        normal *= -1.0;
+    }
+
+    Some(Contact { normal, depth })
 }
 
-pub fn axes(vertexes: &[Vector2]) -> Vec<Vector2> {
-    let mut axes = Vec::new();
+// the widest gap between two polygons on any of their axes, never more than the
+// actual distance between them, zero or less when they overlap
+fn separation(v_one: &[Vector2], v_two: &[Vector2]) -> f32 {
+    axes(v_one)
+        .chain(axes(v_two))
+        .map(|axis| {
+            let p_one = project(v_one, axis);
+            let p_two = project(v_two, axis);
+
+            (p_two.x - p_one.y).max(p_one.x - p_two.y)
+        })
+        .fold(f32::MIN, f32::max)
+}
 
-    for i in 0..vertexes.len() {
-        let v1 = vertexes[i];
-        let v2 = vertexes[if i + 1 == vertexes.len() { 0 } else { i + 1 }];
-        let edge = v1 - v2;
-        // if we don't want the mtv(minimal translation vector), we don't need to normalize
-        let norm = Vector2::new(-edge.y, edge.x).normalized();
+// the normal of every edge, edges without a length don't have one
+fn axes(vertexes: &[Vector2]) -> impl Iterator<Item = Vector2> + '_ {
+    (0..vertexes.len()).filter_map(|i| {
+        let edge = vertexes[i] - vertexes[(i + 1) % vertexes.len()];
 
-        axes.push(norm);
-    }
+        if edge.length_sqr() == 0.0 {
+            return None;
+        }
+
+        Some(Vector2::new(-edge.y, edge.x).normalized())
+    })
+}
 
-    axes
+fn center(vertexes: &[Vector2]) -> Vector2 {
+    let sum = vertexes.iter().fold(Vector2::zero(), |acc, x| acc + *x);
+    sum / vertexes.len() as f32
 }
 
 fn project(vertexes: &[Vector2], axis: Vector2) -> Vector2 {
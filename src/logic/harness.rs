@@ -0,0 +1,188 @@
+use raylib::prelude::*;
+
+use crate::{
+    bus::Bus,
+    commands::{Command, EntityCommands},
+    entities::{Eid, Entities, Entity},
+    forge::Forge,
+    maps::{BackgroundClass, Map, Placement},
+    messages::{LogicMessage, Message},
+    quadtree::QuadTree,
+    random::Random,
+    settings::{Physics, Settings},
+    utils::hash,
+};
+
+use super::Logic;
+
+// runs the simulation without a window, feed it commands tick by tick and look at the state
+pub struct Harness {
+    pub tick: u32,
+    pub entities: Entities,
+    pub forge: Forge,
+    pub logic: Logic,
+    pub quadtree: QuadTree,
+    pub settings: Settings,
+    pub map: Map,
+    pub messages: Vec<LogicMessage>,
+    pub random: Seeded,
+    bus: Bus,
+}
+
+// stands in for the raylib random numbers, same seed gives the same numbers
+pub struct Seeded {
+    state: u32,
+}
+
+impl Seeded {
+    pub fn new(seed: u32) -> Self {
+        Self { state: seed | 1 }
+    }
+}
+
+impl Random for Seeded {
+    fn random(&mut self, range: std::ops::Range<i32>) -> i32 {
+        // xorshift32, the state must never be zero
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+
+        // inclusive on both ends just like raylib
+        let min = range.start.min(range.end) as i64;
+        let max = range.start.max(range.end) as i64;
+
+        (min + self.state as i64 % (max - min + 1)) as i32
+    }
+}
+
+impl Harness {
+    pub fn new(seed: u32, width: i32, height: i32) -> Self {
+        Self {
+            tick: 0,
+            entities: Entities::new(),
+            forge: Forge::new(),
+            logic: Logic::new(),
+            quadtree: QuadTree::new(width, height),
+            settings: Settings {
+                physics: Physics::Arcade,
+                ..Settings::new()
+            },
+            map: Map {
+                name: String::from("harness"),
+                width,
+                height,
+                background: BackgroundClass {
+                    stars: 0,
+                    color: (0, 0, 0),
+                },
+                spawns: vec![(25.0, 25.0)],
+                bases: vec![(25.0, 25.0)],
+                obstacles: Vec::new(),
+                asteroids: 0,
+                pickups: Placement::Random(0),
+                wells: Placement::Random(0),
+                hash: 0,
+                path: None,
+            },
+            messages: Vec::new(),
+            bus: Bus::new(),
+            random: Seeded::new(seed),
+        }
+    }
+
+    pub fn triship(&mut self, position: Vector2, team: u8) -> Eid {
+        let triship = self.forge.triship(position, team, 0, self.settings.physics);
+        self.entities.add(Entity::Triship(triship))
+    }
+
+    pub fn step(&mut self, commands: &[(Eid, &[Command])]) {
+        let commands = commands
+            .iter()
+            .map(|(id, commands)| EntityCommands {
+                id: *id,
+                commands: commands.to_vec().into_boxed_slice(),
+            })
+            .collect::<Vec<_>>();
+
+        self.logic.update(
+            &mut self.bus,
+            &mut self.entities,
+            &commands,
+            &self.forge,
+            &mut self.quadtree,
+            &self.settings,
+            &self.map,
+            &mut self.random,
+        );
+
+        self.messages
+            .extend(self.bus.take().into_iter().filter_map(|x| match x {
+                Message::Logic(x) => Some(x),
+                _ => None,
+            }));
+
+        self.tick += 1;
+    }
+
+    // keep on running the script until the check passes, returns the tick it passed on
+    pub fn run<S, C>(&mut self, ticks: u32, mut script: S, check: C) -> Option<u32>
+    where
+        S: FnMut(u32) -> Vec<(Eid, Vec<Command>)>,
+        C: Fn(&Self) -> bool,
+    {
+        for _ in 0..ticks {
+            let commands = script(self.tick);
+            let commands = commands
+                .iter()
+                .map(|(id, commands)| (*id, commands.as_slice()))
+                .collect::<Vec<_>>();
+
+            self.step(&commands);
+
+            if check(self) {
+                return Some(self.tick);
+            }
+        }
+
+        None
+    }
+
+    // fingerprint of everything that moves or gets hurt, meant for golden state tests
+    pub fn state(&self) -> u32 {
+        let mut v = Vec::new();
+
+        for x in self.entities.triships.iter() {
+            let e = &x.entity;
+            let s = &e.body.state.new;
+            v.extend([s.shape.v1.x, s.shape.v1.y, s.rotation.x, s.rotation.y]);
+            v.extend([
+                e.motion.velocity.x,
+                e.motion.velocity.y,
+                e.life,
+                e.shield.current,
+            ]);
+        }
+
+        for x in self.entities.projectiles.iter() {
+            let v1 = x.entity.body.polygon.vertexes.new[0];
+            v.extend([v1.x, v1.y, x.entity.life]);
+        }
+
+        for x in self.entities.torpedoes.iter() {
+            let v1 = x.entity.body.polygon.vertexes.new[0];
+            v.extend([v1.x, v1.y, x.entity.life]);
+        }
+
+        for x in self.entities.asteroids.iter() {
+            let c = x.entity.body.state.new.shape.center;
+            v.extend([c.x, c.y, x.entity.life]);
+        }
+
+        let bytes = v
+            .iter()
+            .flat_map(|x| x.to_bits().to_be_bytes())
+            .collect::<Vec<_>>();
+
+        hash(&bytes)
+    }
+}
@@ -0,0 +1,68 @@
+use raylib::prelude::*;
+
+use crate::{
+    components::{Acceleratable, Centroidable, Generationable, Renewable},
+    constants::FLAG_CARRIER_SPEED_FACTOR,
+    entities::{Entities, EntityIndex, Flag, Triship},
+};
+
+pub fn update_flags(entities: &mut Entities) {
+    let carried = entities
+        .flags
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, x)| x.entity.carrier.map(|carrier| (idx, carrier)))
+        .collect::<Vec<_>>();
+
+    for (idx, carrier) in carried {
+        let position = match entities.entity(carrier) {
+            Some(EntityIndex::Triship(idx_tri)) => entities.triships[idx_tri]
+                .entity
+                .body
+                .state
+                .new
+                .shape
+                .centroid(),
+            _ => {
+                // carrier is dead, the flag is dropped where it was last seen
+                entities.flags[idx].entity.carrier = None;
+                continue;
+            }
+        };
+
+        place_flag(&mut entities.flags[idx].entity, position);
+    }
+}
+
+pub fn place_flag(flag: &mut Flag, position: Vector2) {
+    let centroid = flag.body.state.new.shape.centroid();
+
+    flag.body.accelerate(position - centroid);
+    flag.body.renew();
+}
+
+pub fn return_flag(flag: &mut Flag) {
+    flag.carrier = None;
+
+    place_flag(flag, flag.home);
+
+    // teleport, we don't want to interpolate the flag across the cosmos
+    flag.body.generation();
+}
+
+pub fn flag_home(flag: &Flag) -> bool {
+    // placing the flag is not exact, close enough is home
+    flag.carrier.is_none() && (flag.body.state.new.shape.centroid() - flag.home).length_sqr() < 1.0
+}
+
+pub fn encumber(triship: &mut Triship) {
+    triship.motion.speed_max *= FLAG_CARRIER_SPEED_FACTOR;
+    triship.boost.speed_max *= FLAG_CARRIER_SPEED_FACTOR;
+    triship.boost.speed_max_old *= FLAG_CARRIER_SPEED_FACTOR;
+}
+
+pub fn unencumber(triship: &mut Triship) {
+    triship.motion.speed_max /= FLAG_CARRIER_SPEED_FACTOR;
+    triship.boost.speed_max /= FLAG_CARRIER_SPEED_FACTOR;
+    triship.boost.speed_max_old /= FLAG_CARRIER_SPEED_FACTOR;
+}
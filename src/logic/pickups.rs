@@ -0,0 +1,87 @@
+use crate::{
+    components::{Effect, EffectKind, Load},
+    constants::{PICKUP_HEALTH, RAPID_FIRE_LIFETIME},
+    entities::{Entities, Pickup, PickupKind, Triship},
+};
+
+pub fn update_pickups(entities: &mut Entities) {
+    entities
+        .pickups
+        .iter_mut()
+        .map(|x| &mut x.entity.respawn.current)
+        .filter(|x| **x != 0)
+        .for_each(|x| *x -= 1);
+}
+
+pub fn update_effects(entities: &mut Entities) {
+    entities.triships.iter_mut().for_each(|x| {
+        let e = &mut x.entity;
+
+        // effects are applied where they matter, e.g. rapid fire when firing,
+        // so there's nothing to reset once they've worn off
+        for effect in e.effects.iter_mut() {
+            effect.lifetime.current -= 1;
+        }
+
+        e.effects.retain(|x| x.lifetime.current != 0);
+    });
+}
+
+pub fn collect(triship: &mut Triship, pickup: &mut Pickup) {
+    // an idle boost has nothing to refill, leave it for someone who can use it
+    if matches!(pickup.kind, PickupKind::Boost) && !triship.boost.active {
+        return;
+    }
+
+    pickup.respawn.current = pickup.respawn.max;
+
+    match pickup.kind {
+        PickupKind::Health => {
+            triship.life = (triship.life + PICKUP_HEALTH).min(triship.life_max);
+        }
+        PickupKind::Boost => {
+            let boost = &mut triship.boost;
+
+            if boost.lifetime.current == 0 {
+                // boost is cooling down, make it ready again
+                boost.cooldown.current = boost.cooldown.max;
+                boost.lifetime.current = boost.lifetime.max;
+                boost.active = false;
+            } else {
+                // boost is in use, keep on going
+                boost.lifetime.current = boost.lifetime.max;
+            }
+        }
+        PickupKind::Ammo => {
+            triship
+                .primary
+                .weapons
+                .iter_mut()
+                .chain(triship.secondary.weapons.iter_mut())
+                .filter_map(|x| x.ammo.as_mut())
+                .for_each(|x| x.current = x.max);
+
+            let flares = &mut triship.countermeasures.ammo;
+            flares.current = flares.max;
+        }
+        PickupKind::RapidFire => {
+            // picking up another one only refreshes the effect
+            if let Some(effect) = triship
+                .effects
+                .iter_mut()
+                .find(|x| matches!(x.kind, EffectKind::RapidFire))
+            {
+                effect.lifetime.current = effect.lifetime.max;
+                return;
+            }
+
+            triship.effects.push(Effect {
+                kind: EffectKind::RapidFire,
+                lifetime: Load {
+                    current: RAPID_FIRE_LIFETIME,
+                    max: RAPID_FIRE_LIFETIME,
+                },
+            });
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use raylib::prelude::*;
+
+use crate::{
+    components::Centroidable,
+    constants::HULL_DAMAGE_EDGES,
+    entities::{Eid, Entities, Triship},
+};
+
+pub fn update_shields(entities: &mut Entities) {
+    entities.triships.iter_mut().for_each(|x| {
+        let s = &mut x.entity.shield;
+
+        if s.flash.current != 0 {
+            s.flash.current -= 1;
+        }
+
+        // wait until we've been left alone for a while before regenerating
+        if s.delay.current != 0 {
+            s.delay.current -= 1;
+            return;
+        }
+
+        s.current = (s.current + s.regen).min(s.max);
+    });
+}
+
+// direction points from the triship towards whatever hit it
+pub fn damage_triship(
+    triship: &mut Triship,
+    damage: f32,
+    direction: Vector2,
+    attacker: Option<Eid>,
+) {
+    let edge = edge(triship, direction);
+
+    if attacker.is_some() {
+        triship.targeting.attacker = attacker;
+    }
+
+    let s = &mut triship.shield;
+
+    let absorbed = damage.min(s.current);
+    let rest = damage - absorbed;
+
+    s.current -= absorbed;
+    s.delay.current = s.delay.max;
+    s.flash.current = s.flash.max;
+    s.edge = edge;
+    s.breached = rest > 0.0;
+
+    triship.life -= rest * HULL_DAMAGE_EDGES[edge % HULL_DAMAGE_EDGES.len()];
+}
+
+// the edge whose outward normal is facing the direction the most
+fn edge(triship: &Triship, direction: Vector2) -> usize {
+    let vertexes = &triship.body.polygon.vertexes.new;
+    let centroid = triship.body.state.new.shape.centroid();
+
+    let mut edge = 0;
+    let mut dot_max = f32::MIN;
+
+    for i in 0..vertexes.len() {
+        let v1 = vertexes[i];
+        let v2 = vertexes[(i + 1) % vertexes.len()];
+        let e = v2 - v1;
+        let mut normal = Vector2::new(e.y, -e.x).normalized();
+
+        // make sure the normal is pointing outwards
+        if normal.dot((v1 + v2) / 2.0 - centroid) < 0.0 {
+            normal *= -1.0;
+        }
+
+        let dot = normal.dot(direction);
+        if dot > dot_max {
+            dot_max = dot;
+            edge = i;
+        }
+    }
+
+    edge
+}
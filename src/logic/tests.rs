@@ -0,0 +1,621 @@
+use super::{bots::Bot, harness::Harness, *};
+use crate::{entities::PickupKind, maps::Placement, settings::Difficulty};
+
+const TICKS: usize = 240;
+
+// a ring of triships and torpedoes all heading for the same spot, plenty of overlap
+fn world(forge: &Forge, churn: usize) -> Entities {
+    let mut entities = Entities::new();
+
+    // give the id map and the entity vectors a different history
+    let ids = (0..churn)
+        .map(|i| {
+            let position = Vector2::new(i as f32, i as f32);
+            let torpedo = forge.torpedo(
+                position,
+                Vector2::one(),
+                Vector2::zero(),
+                Eid::NONE,
+                None,
+                0.0,
+            );
+            entities.add(Entity::Torpedo(torpedo))
+        })
+        .collect::<Vec<_>>();
+
+    for id in ids.iter().skip(1).step_by(2).chain(ids.iter().step_by(2)) {
+        entities.kill(*id);
+    }
+
+    let center = Vector2::new(1000.0, 1000.0);
+
+    for i in 0..16 {
+        let angle = i as f32 / 16.0 * std::f32::consts::TAU;
+        let direction = Vector2::new(angle.cos(), angle.sin());
+        let position = center + direction * 400.0;
+
+        let mut triship = forge.triship(position, i as u8, 0, Physics::Newtonian);
+        triship.motion.velocity = direction * -(2.0 + i as f32 / 4.0);
+        let eid = entities.add(Entity::Triship(triship));
+
+        let position = center + direction * 600.0;
+        let mut torpedo = forge.torpedo(position, direction, Vector2::zero(), eid, Some(eid), 0.0);
+        torpedo.motion.velocity = direction * -(4.0 + i as f32 / 8.0);
+        entities.add(Entity::Torpedo(torpedo));
+    }
+
+    entities
+}
+
+// the whole update with a seed and a script, a bot flies one of the triships
+fn run(seed: u32, churn: usize) -> (Vec<u32>, Vec<EntityIndex>) {
+    let mut harness = Harness::new(seed, 2000, 2000);
+    harness.entities = world(&harness.forge, churn);
+    harness.map.pickups = Placement::Random(4);
+
+    for pickup in harness.forge.pickups(&harness.map, &mut harness.random) {
+        harness.entities.add(Entity::Pickup(pickup));
+    }
+
+    let ids = harness
+        .entities
+        .triships
+        .iter()
+        .map(|x| x.id)
+        .collect::<Vec<_>>();
+    let mut bot = Bot::new(Difficulty::Hard);
+    let mut states = Vec::new();
+
+    for tick in 0..TICKS {
+        let bot_cmds = bot.commands(ids[0], &harness.entities);
+        let mut commands = vec![(ids[0], &bot_cmds[..])];
+
+        for (i, id) in ids.iter().enumerate().skip(1) {
+            let cmds: &[Command] = match (tick + i * 7) % 60 {
+                0..=19 => &[Command::Accelerate, Command::FirePrimary],
+                20..=29 => &[Command::RotateLeft, Command::TargetLock],
+                30..=39 => &[Command::FireSecondary, Command::CycleSecondary],
+                40..=44 => &[Command::Boost, Command::Countermeasure],
+                _ => &[Command::RotateRight, Command::FirePrimary],
+            };
+
+            commands.push((*id, cmds));
+        }
+
+        harness.step(&commands);
+        states.push(harness.state());
+    }
+
+    let dead = harness
+        .messages
+        .iter()
+        .map(|LogicMessage::EntityDead(_, x)| *x)
+        .collect();
+
+    (states, dead)
+}
+
+#[test]
+fn same_seed_same_state() {
+    let (states1, dead1) = run(11, 0);
+    let (states2, dead2) = run(11, 0);
+
+    assert!(!dead1.is_empty());
+    assert_eq!(states1, states2);
+    assert_eq!(dead1, dead2);
+}
+
+#[test]
+fn insertion_history_same_state() {
+    let (states1, dead1) = run(11, 0);
+    let (states2, dead2) = run(11, 37);
+
+    assert_eq!(states1, states2);
+    assert_eq!(dead1, dead2);
+}
+
+#[test]
+fn quadtree_order() {
+    let forge = Forge::new();
+    let entities = world(&forge, 0);
+    let ids = entities
+        .triships
+        .iter()
+        .map(|x| x.id)
+        .chain(entities.torpedoes.iter().map(|x| x.id))
+        .collect::<Vec<_>>();
+
+    let mut forward = QuadTree::new(2000, 2000);
+    ids.iter().for_each(|x| forward.add(*x, &entities));
+
+    let mut backward = QuadTree::new(2000, 2000);
+    ids.iter().rev().for_each(|x| backward.add(*x, &entities));
+
+    let area = Rectangle::new(0.0, 0.0, 2000.0, 2000.0);
+    let mut found = Vec::new();
+    let mut found_backward = Vec::new();
+    forward.get(&area, &mut found);
+    backward.get(&area, &mut found_backward);
+
+    assert_eq!(found.len(), ids.len());
+    assert!(found.windows(2).all(|x| x[0] < x[1]));
+    assert_eq!(found, found_backward);
+}
+
+// kept from tick to tick it has to end up just like one built from scratch
+#[test]
+fn quadtree_incremental() {
+    let forge = Forge::new();
+    let mut entities = world(&forge, 0);
+    let mut quadtree = QuadTree::new(2000, 2000);
+
+    for tick in 0..TICKS {
+        update_body_generation(&mut entities);
+        update_motion(&mut entities, Physics::Newtonian);
+        update_body(&mut entities);
+
+        // something new now and then, and something dies
+        if tick % 10 == 0 {
+            let position = Vector2::new(tick as f32 * 8.0, 1000.0);
+            let projectile =
+                forge.projectile(position, Vector2::one(), Vector2::zero(), Eid::NONE, 1.0);
+            entities.add(Entity::Projectile(projectile));
+        }
+
+        if tick % 15 == 0 {
+            let id = entities.torpedoes[tick % entities.torpedoes.len()].id;
+            entities.kill(id);
+        }
+
+        quadtree.retain(|x| entities.entity(x).is_some());
+        entities.each(|eid, x| {
+            if x.collider().is_some() {
+                quadtree.add(eid, &entities);
+            }
+        });
+
+        let mut fresh = QuadTree::new(2000, 2000);
+        entities.each(|eid, x| {
+            if x.collider().is_some() {
+                fresh.add(eid, &entities);
+            }
+        });
+
+        let area = Rectangle::new(0.0, 0.0, 2000.0, 2000.0);
+        let (mut found, mut found_fresh) = (Vec::new(), Vec::new());
+        quadtree.get(&area, &mut found);
+        fresh.get(&area, &mut found_fresh);
+        assert_eq!(found, found_fresh);
+
+        let (mut pairs, mut pairs_fresh) = (Vec::new(), Vec::new());
+        quadtree.pairs(|x, y| pairs.push((x, y)));
+        fresh.pairs(|x, y| pairs_fresh.push((x, y)));
+        assert_eq!(pairs, pairs_fresh);
+    }
+}
+
+#[test]
+fn layers_decide_pairs() {
+    let forge = Forge::new();
+    let mut entities = Entities::new();
+    let position = Vector2::new(1000.0, 1000.0);
+
+    let triship = forge.triship(position, 0, 0, Physics::Arcade);
+    entities.add(Entity::Triship(triship));
+    entities.add(Entity::Flag(forge.flag(position, 1)));
+
+    // triships fly through each other
+    let triship = forge.triship(position, 1, 0, Physics::Arcade);
+    entities.add(Entity::Triship(triship));
+
+    // right on top of each other, but they don't want to hit one another
+    for _ in 0..2 {
+        let position = Vector2::new(1500.0, 1500.0);
+        let projectile =
+            forge.projectile(position, Vector2::one(), Vector2::zero(), Eid::NONE, 1.0);
+        entities.add(Entity::Projectile(projectile));
+    }
+
+    let mut quadtree = QuadTree::new(2000, 2000);
+    let mut collisions = Vec::new();
+
+    update_body_generation(&mut entities);
+    update_body(&mut entities);
+    update_collision_detection(&mut entities, &mut quadtree, &mut collisions);
+
+    let pairs = collisions
+        .iter()
+        .map(|(eidx1, eidx2, _)| (*eidx1, *eidx2))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        pairs,
+        [
+            (EntityIndex::Triship(0), EntityIndex::Flag(0)),
+            (EntityIndex::Triship(1), EntityIndex::Flag(0))
+        ]
+    );
+}
+
+#[test]
+fn torpedo_lock_hits() {
+    let mut harness = Harness::new(7, 4000, 4000);
+    let hunter = harness.triship(Vector2::new(1000.0, 1000.0), 0);
+    let prey = harness.triship(Vector2::new(1300.0, 1150.0), 1);
+
+    // lock on, give it time to settle and let one go
+    let locked = harness.run(
+        120,
+        |tick| match tick {
+            0 => vec![(hunter, vec![Command::TargetLock])],
+            _ => Vec::new(),
+        },
+        |h| h.entities.triships[0].entity.targeting.timer.current == 0,
+    );
+
+    assert!(locked.is_some());
+
+    let hit = harness.run(
+        200,
+        |tick| {
+            if Some(tick) == locked {
+                vec![(hunter, vec![Command::FireSecondary])]
+            } else {
+                Vec::new()
+            }
+        },
+        |h| {
+            let e = &h.entities.triships[1].entity;
+            e.life + e.shield.current < e.life_max + e.shield.max
+        },
+    );
+
+    assert_eq!(harness.entities.triships[1].id, prey);
+    assert!(hit.is_some());
+    assert!(harness
+        .entities
+        .torpedoes
+        .iter()
+        .all(|x| x.entity.life <= 0.0));
+}
+
+#[test]
+fn dead_triship_notifies() {
+    let mut harness = Harness::new(7, 4000, 4000);
+    let eid = harness.triship(Vector2::new(1000.0, 1000.0), 0);
+
+    harness.step(&[]);
+    assert!(harness.messages.is_empty());
+
+    harness.entities.triships[0].entity.life = 0.0;
+    harness.step(&[]);
+
+    assert!(harness
+        .messages
+        .iter()
+        .any(|x| matches!(x, LogicMessage::EntityDead(id, EntityIndex::Triship(_)) if *id == eid)));
+
+    // gone for good on the next tick
+    harness.step(&[]);
+    assert!(harness.entities.entity(eid).is_none());
+}
+
+#[test]
+fn harness_same_seed_same_state() {
+    let run = |seed| {
+        let mut harness = Harness::new(seed, 4000, 4000);
+        let one = harness.triship(Vector2::new(1000.0, 1000.0), 0);
+        let two = harness.triship(Vector2::new(1300.0, 1200.0), 1);
+
+        harness.run(
+            300,
+            |tick| {
+                let one_cmds = match tick % 40 {
+                    0..=19 => vec![Command::Accelerate, Command::FirePrimary],
+                    _ => vec![Command::RotateLeft, Command::FireSecondary],
+                };
+                let two_cmds = match tick % 30 {
+                    0..=9 => vec![Command::RotateRight, Command::FirePrimary],
+                    _ => vec![Command::Accelerate],
+                };
+
+                vec![(one, one_cmds), (two, two_cmds)]
+            },
+            |_| false,
+        );
+
+        harness.state()
+    };
+
+    assert_eq!(run(11), run(11));
+}
+
+// send something straight across a triship starting just outside of it, a single tick
+// carries it well past the other side at these speeds
+fn shoot(speed: f32, offset: f32, triship_speed: f32, torpedo: bool) -> bool {
+    let mut harness = Harness::new(3, 4000, 4000);
+    harness.triship(Vector2::new(2000.0, 2000.0), 0);
+
+    let t = &mut harness.entities.triships[0].entity;
+    t.dampeners = false;
+    t.motion.speed_max = t.motion.speed_max.max(triship_speed);
+    t.motion.velocity = Vector2::new(-triship_speed, 0.0);
+
+    let b = t.body.polygon.bounds_real.new;
+    let position = Vector2::new(b.x - 20.0, b.y + b.height / 2.0 + offset);
+    let direction = Vector2::new(1.0, 0.0);
+    let velocity = direction * speed;
+
+    if torpedo {
+        let mut e = harness
+            .forge
+            .torpedo(position, direction, velocity, Eid::NONE, None, 1.0);
+        e.motion.speed_max = speed;
+        e.motion.velocity = velocity;
+        harness.entities.add(Entity::Torpedo(e));
+    } else {
+        let mut e = harness
+            .forge
+            .projectile(position, direction, Vector2::zero(), Eid::NONE, 1.0);
+        e.motion.speed_max = speed;
+        e.motion.velocity = velocity;
+        harness.entities.add(Entity::Projectile(e));
+    }
+
+    let hit = |h: &Harness| {
+        h.entities.projectiles.iter().all(|x| x.entity.life <= 0.0)
+            && h.entities.torpedoes.iter().all(|x| x.entity.life <= 0.0)
+    };
+
+    harness.run(4, |_| Vec::new(), hit).is_some()
+}
+
+// every line through the triship, from tip to tip
+fn offsets() -> impl Iterator<Item = f32> {
+    let forge = Forge::new();
+    let triship = forge.triship(Vector2::zero(), 0, 0, Physics::Arcade);
+    let half = triship.body.polygon.bounds_real.new.height / 2.0 - 1.0;
+
+    (0..=(half * 4.0) as i32).map(move |x| x as f32 / 2.0 - half)
+}
+
+#[test]
+fn projectile_no_tunneling() {
+    for speed in [30.0, 60.0, 120.0, 240.0] {
+        for offset in offsets() {
+            assert!(shoot(speed, offset, 0.0, false), "{} {}", speed, offset);
+        }
+    }
+}
+
+#[test]
+fn projectile_head_on_no_tunneling() {
+    // projectile and triship both at their limits, boost included
+    for offset in offsets() {
+        assert!(shoot(30.0, offset, 46.0, false), "{}", offset);
+    }
+}
+
+#[test]
+fn torpedo_no_tunneling() {
+    for speed in [30.0, 60.0, 120.0] {
+        for offset in offsets() {
+            assert!(shoot(speed, offset, 20.0, true), "{} {}", speed, offset);
+        }
+    }
+}
+
+#[test]
+fn contact_depth_and_normal() {
+    let square = |x: f32, y: f32, size: f32| {
+        vec![
+            Vector2::new(x, y),
+            Vector2::new(x + size, y),
+            Vector2::new(x + size, y + size),
+            Vector2::new(x, y + size),
+        ]
+    };
+
+    let c = contact(&square(0.0, 0.0, 10.0), &square(7.0, 1.0, 10.0)).expect("overlap");
+    assert_eq!(c.normal, Vector2::new(1.0, 0.0));
+    assert!((c.depth - 3.0).abs() < 0.001);
+    assert!((c.mtv().x - 3.0).abs() < 0.001);
+
+    // flipped around the normal has to point the other way
+    let c = contact(&square(7.0, 1.0, 10.0), &square(0.0, 0.0, 10.0)).expect("overlap");
+    assert_eq!(c.normal, Vector2::new(-1.0, 0.0));
+
+    assert!(contact(&square(0.0, 0.0, 10.0), &square(11.0, 0.0, 10.0)).is_none());
+
+    // any number of vertexes, a hexagon resting on a triangle
+    let hexagon = (0..6)
+        .map(|i| {
+            let angle = i as f32 / 6.0 * std::f32::consts::TAU;
+            Vector2::new(angle.cos(), angle.sin()) * 10.0
+        })
+        .collect::<Vec<_>>();
+    let triangle = [
+        Vector2::new(-20.0, 8.0),
+        Vector2::new(20.0, 8.0),
+        Vector2::new(0.0, 30.0),
+    ];
+
+    let c = contact(&hexagon, &triangle).expect("overlap");
+    assert!(c.normal.y > 0.99);
+    assert!((c.depth - (10.0 * 60f32.to_radians().sin() - 8.0)).abs() < 0.001);
+}
+
+// fire at a long wall, steep enough or not
+fn ricochet(velocity: Vector2) -> (f32, Vector2) {
+    let mut harness = Harness::new(5, 4000, 4000);
+
+    let wall = harness.forge.obstacle(&[
+        (2000.0, 1000.0),
+        (2100.0, 1000.0),
+        (2100.0, 3000.0),
+        (2000.0, 3000.0),
+    ]);
+    harness.entities.add(Entity::Obstacle(wall));
+
+    let position = Vector2::new(1950.0, 2000.0);
+    let mut e = harness.forge.projectile(
+        position,
+        velocity.normalized(),
+        Vector2::zero(),
+        Eid::NONE,
+        1.0,
+    );
+    e.motion.speed_max = velocity.length();
+    e.motion.velocity = velocity;
+    harness.entities.add(Entity::Projectile(e));
+
+    harness.run(
+        10,
+        |_| Vec::new(),
+        |h| {
+            let e = &h.entities.projectiles[0].entity;
+            e.life <= 0.0 || e.motion.velocity.x < 0.0
+        },
+    );
+
+    let e = &harness.entities.projectiles[0].entity;
+    (e.life, e.motion.velocity)
+}
+
+#[test]
+fn projectile_ricochets() {
+    // glancing blow, comes back out the way it went in
+    let (life, velocity) = ricochet(Vector2::new(5.0, 25.0));
+    assert!(life > 0.0);
+    assert!(velocity.x < 0.0);
+    assert!((velocity.y - 25.0).abs() < 0.001);
+
+    // straight on, it goes off
+    let (life, _) = ricochet(Vector2::new(25.0, 5.0));
+    assert!(life <= 0.0);
+}
+
+// a couple of bots going at each other, their commands come from the state alone,
+// returns how many ticks someone got hurt on
+fn duel(seed: u32, difficulty: Difficulty, ticks: u32) -> (Harness, u32) {
+    let mut harness = Harness::new(seed, 4000, 4000);
+
+    let ids = [
+        harness.triship(Vector2::new(1500.0, 2000.0), 0),
+        harness.triship(Vector2::new(2500.0, 2100.0), 1),
+    ];
+    let mut bots = [Bot::new(difficulty), Bot::new(difficulty)];
+    let mut hits = 0;
+
+    // shields come back, so keep an eye on them the whole time
+    let health = |h: &Harness| {
+        h.entities
+            .triships
+            .iter()
+            .map(|x| x.entity.life + x.entity.shield.current)
+            .sum::<f32>()
+    };
+
+    for _ in 0..ticks {
+        let commands = ids
+            .iter()
+            .zip(bots.iter_mut())
+            .map(|(id, bot)| (*id, bot.commands(*id, &harness.entities)))
+            .collect::<Vec<_>>();
+        let commands = commands
+            .iter()
+            .map(|(id, commands)| (*id, &commands[..]))
+            .collect::<Vec<_>>();
+
+        let before = health(&harness);
+        harness.step(&commands);
+
+        if health(&harness) < before {
+            hits += 1;
+        }
+    }
+
+    (harness, hits)
+}
+
+#[test]
+fn bots_fight() {
+    for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+        let (_, hits) = duel(3, difficulty, 400);
+        assert!(hits > 0, "{:?}", difficulty);
+    }
+}
+
+#[test]
+fn bots_deterministic() {
+    let (one, _) = duel(7, Difficulty::Hard, 300);
+    let (two, _) = duel(7, Difficulty::Hard, 300);
+
+    assert_eq!(one.state(), two.state());
+}
+
+// bases all over the map leave no room for wells, they have to be left out instead of
+// retrying forever
+#[test]
+fn wells_crowded_map() {
+    let mut harness = Harness::new(5, 4000, 4000);
+    harness.map.wells = Placement::Random(3);
+    harness.map.bases = (0..=8)
+        .flat_map(|x| (0..=8).map(move |y| (x as f32 * 500.0, y as f32 * 500.0)))
+        .collect();
+
+    let wells = harness.forge.wells(&harness.map, &mut harness.random);
+    assert!(wells.is_empty());
+
+    harness.map.bases = vec![(25.0, 25.0)];
+
+    let wells = harness.forge.wells(&harness.map, &mut harness.random);
+    assert_eq!(wells.len(), 3);
+}
+
+// commands can end up addressed to anything, whatever can't follow them just ignores them
+#[test]
+fn commands_wrong_kind() {
+    let mut harness = Harness::new(9, 4000, 4000);
+    let mine = harness
+        .forge
+        .mine(Vector2::new(2000.0, 2000.0), Vector2::one(), Eid::NONE, 1.0);
+    let eid = harness.entities.add(Entity::Mine(mine));
+
+    let commands = [
+        Command::Accelerate,
+        Command::Decelerate,
+        Command::RotateLeft,
+        Command::RotateRight,
+        Command::FirePrimary,
+        Command::Boost,
+        Command::FireSecondary,
+        Command::TargetLock,
+        Command::CyclePrimary,
+        Command::CycleSecondary,
+        Command::Countermeasure,
+        Command::TargetPrevious,
+        Command::TargetCrosshair,
+        Command::TargetWeakest,
+        Command::TargetAttacker,
+        Command::Dampeners,
+    ];
+
+    harness.step(&[(eid, &commands)]);
+    assert!(harness.entities.mine(eid).is_some());
+}
+
+// a pickup that can't do anything for the triship stays where it is
+#[test]
+fn boost_pickup_needs_boost() {
+    let forge = Forge::new();
+    let mut triship = forge.triship(Vector2::zero(), 0, 0, Physics::Arcade);
+    let mut pickup = forge.pickup(Vector2::zero(), PickupKind::Boost);
+
+    collect(&mut triship, &mut pickup);
+    assert_eq!(pickup.respawn.current, 0);
+
+    triship.boost.active = true;
+    collect(&mut triship, &mut pickup);
+    assert_eq!(pickup.respawn.current, pickup.respawn.max);
+}
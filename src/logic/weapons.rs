@@ -0,0 +1,142 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    constants::WEAPON_HEAT_COOLING,
+    entities::{Eid, Entities, Entity, EntityIndex},
+    forge::Forge,
+    random::Random,
+};
+
+use super::{
+    collisions::{explode_triship_if_dead, split_asteroid_if_dead},
+    shields::damage_triship,
+};
+
+pub fn update_weapons(entities: &mut Entities) {
+    entities
+        .triships
+        .iter_mut()
+        .flat_map(|x| {
+            let e = &mut x.entity;
+            e.primary
+                .weapons
+                .iter_mut()
+                .chain(e.secondary.weapons.iter_mut())
+        })
+        .for_each(|x| {
+            if x.cooldown.current != 0 {
+                x.cooldown.current -= 1;
+            }
+
+            let heat = &mut x.heat;
+            heat.current = heat.current.saturating_sub(WEAPON_HEAT_COOLING);
+
+            // weapon has fully cooled down, we're good to go again
+            if heat.current == 0 {
+                heat.overheated = false;
+            }
+        });
+}
+
+pub fn update_countermeasures(entities: &mut Entities) {
+    entities
+        .triships
+        .iter_mut()
+        .map(|x| &mut x.entity.countermeasures.cooldown.current)
+        .filter(|x| **x != 0)
+        .for_each(|x| *x -= 1);
+}
+
+pub fn update_mine_timers(entities: &mut Entities) {
+    entities
+        .mines
+        .iter_mut()
+        .map(|x| &mut x.entity.timer_inactive)
+        .filter(|x| **x != 0)
+        .for_each(|x| *x -= 1);
+}
+
+pub fn update_beams(
+    entities: &mut Entities,
+    dead: &mut BTreeSet<Eid>,
+    forge: &Forge,
+    h: &mut dyn Random,
+) {
+    let hits = entities
+        .beams
+        .iter_mut()
+        .filter_map(|x| {
+            let b = &mut x.entity;
+            b.target
+                .take()
+                .map(|target| (target, b.origin, b.end, b.damage, b.owner_id))
+        })
+        .collect::<Vec<_>>();
+
+    for (target, origin, end, damage, owner_id) in hits {
+        let Some(eidx) = entities.entity(target) else {
+            continue;
+        };
+
+        match eidx {
+            EntityIndex::Triship(idx) => {
+                let t = &mut entities.triships[idx].entity;
+
+                // already dead this tick
+                if t.life <= 0.0 {
+                    continue;
+                }
+
+                damage_triship(t, damage, origin - end, Some(owner_id));
+
+                for explosion in forge.explosion_projectile(end, h) {
+                    entities.add(Entity::Explosion(explosion));
+                }
+
+                explode_triship_if_dead(idx, forge, entities, h);
+            }
+            EntityIndex::Asteroid(idx) => {
+                let a = &mut entities.asteroids[idx].entity;
+
+                // asteroid has already been split this tick
+                if a.life <= 0.0 {
+                    continue;
+                }
+
+                a.life -= damage;
+
+                for explosion in forge.explosion_projectile(end, h) {
+                    entities.add(Entity::Explosion(explosion));
+                }
+
+                split_asteroid_if_dead(idx, forge, entities, h);
+            }
+            EntityIndex::Torpedo(idx) => {
+                entities.torpedoes[idx].entity.life = 0.0;
+
+                for explosion in forge.explosion_torpedo(end, h) {
+                    entities.add(Entity::Explosion(explosion));
+                }
+            }
+            EntityIndex::Mine(idx) => {
+                entities.mines[idx].entity.life = 0.0;
+
+                for explosion in forge.explosion_torpedo(end, h) {
+                    entities.add(Entity::Explosion(explosion));
+                }
+            }
+            // nothing else takes damage
+            _ => continue,
+        }
+    }
+
+    entities.beams.iter_mut().for_each(|x| {
+        let lifetime = &mut x.entity.lifetime;
+
+        if lifetime.current == 0 {
+            dead.insert(x.id);
+        } else {
+            lifetime.current -= 1;
+        }
+    });
+}
@@ -9,12 +9,16 @@ mod entities;
 mod forge;
 mod logic;
 mod logs;
+mod maps;
 mod math;
 mod messages;
 mod net;
 mod packets;
 mod quadtree;
+mod random;
 mod render;
+mod settings;
+mod ships;
 mod states;
 mod systems;
 mod utils;
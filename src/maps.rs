@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::MAPS_PATH,
+    entities::{PickupKind, WellKind},
+    utils::hash,
+};
+
+#[cfg(test)]
+mod tests;
+
+pub struct Maps {
+    pub maps: Vec<Map>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Map {
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    pub background: BackgroundClass,
+    pub spawns: Vec<(f32, f32)>,
+    pub bases: Vec<(f32, f32)>,
+    pub obstacles: Vec<Vec<(f32, f32)>>,
+    pub asteroids: usize,
+    pub pickups: Placement<PickupKind>,
+    pub wells: Placement<WellKind>,
+    #[serde(skip)]
+    pub hash: u32,
+    // the file it was loaded from and gets saved back to, none until a new one is saved
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BackgroundClass {
+    pub stars: usize,
+    pub color: (u8, u8, u8),
+}
+
+// either let the seed scatter an amount of them or put them exactly where we want
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Placement<T> {
+    Random(usize),
+    Fixed(Vec<(f32, f32, T)>),
+}
+
+impl Maps {
+    pub fn load() -> Self {
+        let entries = match std::fs::read_dir(MAPS_PATH) {
+            Ok(entries) => entries,
+            Err(error) => panic!("wtf maps {}: {}", MAPS_PATH, error),
+        };
+
+        let mut paths = entries
+            .filter_map(|x| x.ok())
+            .map(|x| x.path())
+            .filter(|x| x.extension().is_some_and(|x| x == "ron"))
+            .collect::<Vec<_>>();
+
+        // everyone has to end up with the same order
+        paths.sort();
+
+        let maps = paths
+            .iter()
+            .map(|path| {
+                let data = match std::fs::read_to_string(path) {
+                    Ok(data) => data,
+                    Err(error) => panic!("wtf map {}: {}", path.display(), error),
+                };
+
+                let file = path.clone();
+                let path = path.display();
+
+                let mut map: Map = match ron::from_str(&data) {
+                    Ok(map) => map,
+                    Err(error) => panic!("wtf map {}: {}", path, error),
+                };
+
+                if map.width <= 0 || map.height <= 0 {
+                    panic!("wtf map {}: no room", path);
+                }
+
+                if map.spawns.is_empty() {
+                    panic!("wtf map {}: no spawns", path);
+                }
+
+                // every team needs a base to spawn at in capture the flag
+                if map.bases.is_empty() {
+                    panic!("wtf map {}: no bases", path);
+                }
+
+                // the collision detection only works with convex polygons
+                if let Some(i) = map.obstacles.iter().position(|x| !convex(x)) {
+                    panic!("wtf map {}: obstacle {} is not convex", path, i);
+                }
+
+                // hash the raw file, everyone must agree on every single byte
+                map.hash = hash(data.as_bytes());
+                map.path = Some(file);
+
+                map
+            })
+            .collect::<Vec<_>>();
+
+        if maps.is_empty() {
+            panic!("wtf maps {}: no maps", MAPS_PATH);
+        }
+
+        Self { maps }
+    }
+}
+
+impl Map {
+    // an empty cosmos with the least a map needs, somewhere to spawn and a base per team
+    pub fn blank(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            width: 8000,
+            height: 6000,
+            background: BackgroundClass {
+                stars: 96,
+                color: (0, 0, 0),
+            },
+            spawns: vec![(4000.0, 3000.0)],
+            bases: vec![(1200.0, 3000.0), (6800.0, 3000.0)],
+            obstacles: Vec::new(),
+            asteroids: 0,
+            pickups: Placement::Fixed(Vec::new()),
+            wells: Placement::Fixed(Vec::new()),
+            hash: 0,
+            path: None,
+        }
+    }
+
+    // a blank one with the first untitled name that doesn't have a file yet
+    pub fn untitled() -> Self {
+        let mut i = 1;
+
+        while file(&format!("untitled-{}", i)).exists() {
+            i += 1;
+        }
+
+        Self::blank(&format!("untitled-{}", i))
+    }
+
+    pub fn to_ron(&self) -> String {
+        match ron::ser::to_string_pretty(self, PrettyConfig::new()) {
+            Ok(data) => data,
+            Err(error) => panic!("wtf map {}: {}", self.name, error),
+        }
+    }
+
+    // the hash is left out, it's taken from the file the next time the maps are loaded,
+    // a new map gets a file named after it but never takes over another map's file
+    pub fn save(&mut self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => {
+                if !stem(&self.name) {
+                    return Err(format!("{}: not a file name", self.name));
+                }
+
+                let path = file(&self.name);
+
+                if path.exists() {
+                    return Err(format!("{}: already exists", path.display()));
+                }
+
+                path
+            }
+        };
+
+        std::fs::write(&path, self.to_ron()).map_err(|x| format!("{}: {}", path.display(), x))?;
+        self.path = Some(path);
+
+        Ok(())
+    }
+}
+
+fn file(name: &str) -> PathBuf {
+    Path::new(MAPS_PATH).join(format!("{}.ron", name))
+}
+
+// nothing that could lead out of the maps directory, no separators and no dots
+fn stem(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_')
+}
+
+fn convex(vertexes: &[(f32, f32)]) -> bool {
+    if vertexes.len() < 3 {
+        return false;
+    }
+
+    // all the turns have to go the same way
+    let mut sign = 0.0;
+
+    for i in 0..vertexes.len() {
+        let (x1, y1) = vertexes[i];
+        let (x2, y2) = vertexes[(i + 1) % vertexes.len()];
+        let (x3, y3) = vertexes[(i + 2) % vertexes.len()];
+
+        let cross = (x2 - x1) * (y3 - y2) - (y2 - y1) * (x3 - x2);
+
+        if cross == 0.0 {
+            continue;
+        }
+
+        if sign == 0.0 {
+            sign = cross.signum();
+        } else if cross.signum() != sign {
+            return false;
+        }
+    }
+
+    sign != 0.0
+}
@@ -0,0 +1,36 @@
+use super::*;
+
+// whatever the editor writes has to come back exactly the same
+#[test]
+fn maps_round_trip() {
+    let maps = Maps::load();
+
+    for map in maps.maps.iter().chain([&Map::blank("blank")]) {
+        let data = map.to_ron();
+        let back: Map = ron::from_str(&data).expect("wtf round trip");
+
+        assert_eq!(back.to_ron(), data);
+        assert_eq!(back.name, map.name);
+        assert_eq!(back.spawns, map.spawns);
+        assert_eq!(back.obstacles, map.obstacles);
+        assert!(back.obstacles.iter().all(|x| convex(x)));
+    }
+}
+
+// a new map only ever gets a file of its own right in the maps directory
+#[test]
+fn save_rejects_other_files() {
+    for name in ["", "../x", "a/b", "a\\b", ".", "x.ron"] {
+        assert!(Map::blank(name).save().is_err(), "{}", name);
+    }
+
+    let maps = Maps::load();
+
+    for map in maps.maps.iter() {
+        let Some(stem) = map.path.as_ref().and_then(|x| x.file_stem()) else {
+            panic!("wtf map {} has no file", map.name);
+        };
+
+        assert!(Map::blank(&stem.to_string_lossy()).save().is_err());
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{commands::Command, entities::EntityIndex, states::State};
+use crate::{
+    commands::Command,
+    entities::{Eid, EntityIndex},
+    settings::Settings,
+    states::State,
+};
 
 #[derive(Debug)]
 pub enum Message {
@@ -10,7 +15,7 @@ pub enum Message {
 
 #[derive(Debug)]
 pub enum LogicMessage {
-    EntityDead(usize, EntityIndex),
+    EntityDead(Eid, EntityIndex),
 }
 
 #[derive(Debug)]
@@ -46,7 +51,7 @@ pub enum StateRequestMessage {
 #[derive(Debug)]
 pub enum NetMessage {
     Request(NetRequestMessage),
-    Synchronize(u32, u32, Box<[u32]>),
+    Synchronize(u32, u32, Settings, Box<[u32]>),
     Hosted,
     Connected,
     Disconnected,
@@ -64,6 +69,8 @@ pub enum NetRequestMessage {
     Disconnect,
     Commands(u32, Box<[Command]>),
     TogglePause,
+    Settings(Settings),
+    Class(u8),
 }
 
 impl Into<Message> for StateMessage {
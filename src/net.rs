@@ -1,8 +1,11 @@
 use crate::{
     bus::Bus,
     commands::Command,
+    maps::Maps,
     messages::{Message, NetMessage, NetRequestMessage},
     packets::{ClientPacket, ServerPacket},
+    settings::Settings,
+    ships::Ships,
 };
 
 use raylib::prelude::*;
@@ -13,6 +16,9 @@ use redpine::{
 pub struct System {
     actions: Vec<Action>,
     seed: u32,
+    settings: Settings,
+    ships: u32,
+    maps: Vec<(String, u32)>,
     server: Option<Server>,
     client: Option<Client>,
     clients: Vec<PeerHandle>,
@@ -29,15 +35,32 @@ enum Action {
     Disconnect,
     Shutdown,
     TogglePause,
+    Settings(Settings),
+    Class(u8),
 }
 
 const PORT: u16 = 1337;
 
 impl System {
     pub fn new() -> Self {
+        let ships = Ships::load().hash;
+        let maps = Maps::load()
+            .maps
+            .iter()
+            .map(|x| (x.name.clone(), x.hash))
+            .collect::<Vec<_>>();
+
         Self {
             actions: Vec::new(),
             seed: 0,
+            settings: Settings {
+                ships,
+                map: maps[0].0.clone(),
+                map_hash: maps[0].1,
+                ..Settings::new()
+            },
+            ships,
+            maps,
             server: None,
             client: None,
             clients: Vec::new(),
@@ -71,10 +94,34 @@ impl System {
                 }
                 NetRequestMessage::Disconnect => self.actions.push(Action::Disconnect),
                 NetRequestMessage::TogglePause => self.actions.push(Action::TogglePause),
+                NetRequestMessage::Settings(settings) => {
+                    // can only change the settings if we're hosting
+                    if self.server.is_some() {
+                        self.actions.push(Action::Settings(settings.clone()));
+                    }
+                }
+                NetRequestMessage::Class(class) => self.actions.push(Action::Class(*class)),
             }
         }
     }
 
+    // maps can be saved in the editor while we're running, a map that changed since
+    // would get us thrown out by the map check
+    fn load_maps(&mut self) {
+        self.maps = Maps::load()
+            .maps
+            .iter()
+            .map(|x| (x.name.clone(), x.hash))
+            .collect();
+
+        let map = (self.settings.map.clone(), self.settings.map_hash);
+
+        if !self.maps.contains(&map) {
+            self.settings.map = self.maps[0].0.clone();
+            self.settings.map_hash = self.maps[0].1;
+        }
+    }
+
     fn client(&mut self, h: &mut RaylibHandle, bus: &mut Bus) {
         let Some(client) = self.client.as_mut() else {
             return;
@@ -89,14 +136,28 @@ impl System {
                     bus.send(NetMessage::Disconnected);
                 }
                 ClientEvent::Receive(data) => match ClientPacket::from_bytes(&data) {
-                    ClientPacket::Synchronize(seed, cid, cids) => {
+                    ClientPacket::Synchronize(seed, cid, settings, cids) => {
+                        // we can't play with different ship definitions, we'd go out of sync
+                        if settings.ships != self.ships {
+                            self.actions.push(Action::Disconnect);
+                            continue;
+                        }
+
+                        // same goes for the map, we need the exact same one
+                        let map = (settings.map.clone(), settings.map_hash);
+                        if !self.maps.contains(&map) {
+                            self.actions.push(Action::Disconnect);
+                            continue;
+                        }
+
                         self.seed = seed;
+                        self.settings = settings.clone();
                         self.client_id = cid;
                         self.client_ids = cids.to_vec();
 
                         h.set_random_seed(self.seed);
 
-                        bus.send(NetMessage::Synchronize(seed, cid, cids));
+                        bus.send(NetMessage::Synchronize(seed, cid, settings, cids));
                     }
                     ClientPacket::Commands(cid, tick, cmds) => {
                         bus.send(NetMessage::Commands(cid, tick, cmds.clone()));
@@ -129,40 +190,31 @@ impl System {
                     self.client_ids.push(cid);
 
                     // send a sync to all clients
-                    for client in self.clients.iter_mut() {
-                        client.send(
-                            ClientPacket::Synchronize(
-                                self.seed,
-                                client.id(),
-                                self.client_ids.clone().into_boxed_slice(),
-                            )
-                            .to_bytes(),
-                            SendMode::Reliable,
-                        );
-                    }
+                    synchronize(
+                        &mut self.clients,
+                        self.seed,
+                        &self.settings,
+                        &self.client_ids,
+                    );
                 }
                 ServerEvent::Disconnect(peer) => {
                     let cid = peer.id();
 
                     self.clients.retain(|x| x.id() != cid);
                     self.client_ids.retain(|x| *x != cid);
+                    self.settings.classes.retain(|(x, _)| *x != cid);
 
                     // we've been disconnected from our own server, let's kill it
                     if cid == self.client_id {
                         self.actions.push(Action::Shutdown);
                     } else {
                         // send a sync to all clients
-                        for client in self.clients.iter_mut() {
-                            client.send(
-                                ClientPacket::Synchronize(
-                                    self.seed,
-                                    client.id(),
-                                    self.client_ids.clone().into_boxed_slice(),
-                                )
-                                .to_bytes(),
-                                SendMode::Reliable,
-                            );
-                        }
+                        synchronize(
+                            &mut self.clients,
+                            self.seed,
+                            &self.settings,
+                            &self.client_ids,
+                        );
                     }
                 }
                 ServerEvent::Receive(peer, data) => match ServerPacket::from_bytes(&data) {
@@ -184,6 +236,20 @@ impl System {
                             );
                         }
                     }
+                    ServerPacket::Class(class) => {
+                        let cid = peer.id();
+
+                        self.settings.classes.retain(|(x, _)| *x != cid);
+                        self.settings.classes.push((cid, class));
+
+                        // send a sync to all clients so everyone knows about the new class
+                        synchronize(
+                            &mut self.clients,
+                            self.seed,
+                            &self.settings,
+                            &self.client_ids,
+                        );
+                    }
                 },
                 ServerEvent::Error(_peer, error) => match error {
                     _ => panic!("wtf server error {:?}", error),
@@ -199,6 +265,7 @@ impl System {
                     bus.send(NetMessage::Synchronize(
                         self.seed,
                         self.client_id,
+                        self.settings.clone(),
                         self.client_ids.clone().into_boxed_slice(),
                     ));
                 }
@@ -219,6 +286,7 @@ impl System {
                         },
                     ) {
                         self.server = Some(server);
+                        self.load_maps();
                         self.seed = h.get_random_value::<i32>(0..i32::MAX) as u32;
 
                         h.set_random_seed(self.seed);
@@ -229,6 +297,7 @@ impl System {
                 Action::Connect(host) => {
                     if let Ok(client) = Client::connect((host, PORT)) {
                         self.client = Some(client);
+                        self.load_maps();
                     }
                 }
                 Action::Start => {
@@ -260,7 +329,38 @@ impl System {
                         client.send(ServerPacket::TogglePause.to_bytes(), SendMode::Reliable);
                     }
                 }
+                Action::Class(class) => {
+                    if let Some(client) = self.client.as_mut() {
+                        client.send(ServerPacket::Class(class).to_bytes(), SendMode::Reliable);
+                    }
+                }
+                Action::Settings(settings) => {
+                    self.settings = settings;
+
+                    // send a sync to all clients so everyone knows about the new settings
+                    synchronize(
+                        &mut self.clients,
+                        self.seed,
+                        &self.settings,
+                        &self.client_ids,
+                    );
+                }
             }
         }
     }
 }
+
+fn synchronize(clients: &mut [PeerHandle], seed: u32, settings: &Settings, client_ids: &[u32]) {
+    for client in clients.iter_mut() {
+        client.send(
+            ClientPacket::Synchronize(
+                seed,
+                client.id(),
+                settings.clone(),
+                client_ids.to_vec().into_boxed_slice(),
+            )
+            .to_bytes(),
+            SendMode::Reliable,
+        );
+    }
+}
@@ -1,7 +1,7 @@
-use crate::commands::Command;
+use crate::{commands::Command, settings::Settings};
 
 pub enum ClientPacket {
-    Synchronize(u32, u32, Box<[u32]>),
+    Synchronize(u32, u32, Settings, Box<[u32]>),
     Commands(u32, u32, Box<[Command]>),
     Start,
     TogglePause(u32),
@@ -10,12 +10,14 @@ pub enum ClientPacket {
 pub enum ServerPacket {
     Commands(u32, Box<[Command]>),
     TogglePause,
+    Class(u8),
 }
 
 const SYNCHRONIZE: u8 = 1;
 const COMMANDS: u8 = 2;
 const START: u8 = 3;
 const TOGGLE_PAUSE: u8 = 4;
+const CLASS: u8 = 5;
 
 impl ClientPacket {
     pub fn from_bytes(bytes: &[u8]) -> Self {
@@ -31,6 +33,12 @@ impl ClientPacket {
                 let (cid, data) = data.split_at(4);
                 let cid = u32::from_be_bytes(cid.try_into().expect("wtf sync cid"));
 
+                let len = data.first_chunk::<2>().expect("wtf sync settings");
+                let len = u16::from_be_bytes(*len);
+
+                let (settings, data) = data.split_at(len as usize);
+                let settings = Settings::from_bytes(settings);
+
                 let mut cids = Vec::new();
                 let mut read = 0;
 
@@ -43,7 +51,7 @@ impl ClientPacket {
                     cids.push(cid);
                 }
 
-                ClientPacket::Synchronize(seed, cid, cids.into_boxed_slice())
+                ClientPacket::Synchronize(seed, cid, settings, cids.into_boxed_slice())
             }
             COMMANDS => {
                 let (cid, data) = data.split_at(4);
@@ -85,10 +93,11 @@ impl ClientPacket {
         let mut bytes = Vec::new();
 
         match self {
-            ClientPacket::Synchronize(seed, cid, cids) => {
+            ClientPacket::Synchronize(seed, cid, settings, cids) => {
                 bytes.push(SYNCHRONIZE);
                 bytes.extend_from_slice(&seed.to_be_bytes());
                 bytes.extend_from_slice(&cid.to_be_bytes());
+                bytes.extend_from_slice(&settings.to_bytes());
 
                 for cid in cids.iter() {
                     bytes.extend_from_slice(&cid.to_be_bytes());
@@ -145,6 +154,13 @@ impl ServerPacket {
                 ServerPacket::Commands(tick, cmds.into_boxed_slice())
             }
             TOGGLE_PAUSE => ServerPacket::TogglePause,
+            CLASS => {
+                let Some(class) = data.first() else {
+                    panic!("wtf class");
+                };
+
+                ServerPacket::Class(*class)
+            }
 
             _ => panic!("wtf ptype {}", ptype),
         }
@@ -165,6 +181,10 @@ impl ServerPacket {
             ServerPacket::TogglePause => {
                 bytes.push(TOGGLE_PAUSE);
             }
+            ServerPacket::Class(class) => {
+                bytes.push(CLASS);
+                bytes.push(*class);
+            }
         }
 
         bytes.into_boxed_slice()
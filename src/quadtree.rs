@@ -1,27 +1,43 @@
-use std::collections::HashSet;
-
-use crate::entities::{Entities, EntityIndex};
+use crate::entities::{Eid, Entities, EntityIndex};
 use raylib::prelude::*;
 
-const MAX_SIZE: usize = 4;
-const MAX_DEPTH: u8 = 8;
+#[cfg(test)]
+mod benches;
 
-// TODO: could probably solve this in a better way instead of having boxed stuff
+const MAX_DEPTH: u32 = 8;
+const CELL_SIZE: f32 = 512.0;
 
+// a complete quadtree laid out flat level by level, so nodes never have to be split or boxed,
+// every entity lives in the deepest node that fully contains it, which means it's in there once,
+// and it stays there from tick to tick until it leaves the node or dies
 pub struct QuadTree {
-    pub root: Node,
     initial: Rectangle,
+    depth: u32,
+    // every node keeps its capacity, after the first few ticks nothing gets allocated anymore
+    nodes: Vec<Vec<Entry>>,
+    // nodes that have something in them, so we don't have to go through all of them
+    used: Vec<u32>,
+    // where every entity in the tree is, by the slot of its handle
+    locations: Vec<Option<Location>>,
+    // sorted before anyone gets to see them, the order in the nodes depends on the history
+    pairs: Vec<(EntityIndex, EntityIndex)>,
 }
 
-pub struct Node {
-    pub node_type: NodeType,
-    dimension: Rectangle,
-    depth: u8,
+#[derive(Clone, Copy)]
+struct Entry {
+    eid: Eid,
+    eidx: EntityIndex,
+    bounds: Rectangle,
+    level: u32,
+    x: u32,
+    y: u32,
 }
 
-pub enum NodeType {
-    Leaf(Vec<EntityIndex>),
-    Branch([Box<Node>; 4]),
+#[derive(Clone, Copy)]
+struct Location {
+    eid: Eid,
+    node: u32,
+    position: u32,
 }
 
 impl QuadTree {
@@ -33,180 +49,259 @@ impl QuadTree {
             height: height as f32,
         };
 
+        // go as deep as we need for the smallest nodes to be about the size of a cell
+        let mut depth = 0;
+        while depth < MAX_DEPTH
+            && initial.width.max(initial.height) / (1 << depth) as f32 > CELL_SIZE
+        {
+            depth += 1;
+        }
+
+        let nodes = offset(depth + 1) as usize;
+
         Self {
-            root: Node {
-                node_type: NodeType::Leaf(Vec::new()),
-                dimension: initial,
-                depth: 0,
-            },
             initial,
+            depth,
+            nodes: vec![Vec::new(); nodes],
+            used: Vec::new(),
+            locations: Vec::new(),
+            pairs: Vec::new(),
         }
     }
 
-    pub fn reset(&mut self) {
-        self.root = Node {
-            node_type: NodeType::Leaf(Vec::new()),
-            dimension: self.initial,
-            depth: 0,
+    // adds the entity, or if it's already in here, catches up with where it went
+    pub fn add(&mut self, eid: Eid, entities: &Entities) {
+        let Some(eidx) = entities.entity(eid) else {
+            return;
         };
-    }
 
-    pub fn add(&mut self, eid: usize, entities: &Entities) {
-        let eidx = match entities.entity(eid) {
-            Some(eidx) => eidx,
-            None => return,
+        // nothing to collide with
+        let Some(polygon) = entities.archetype(eidx).collider() else {
+            return;
         };
 
-        let bounds = bounds(eidx, entities);
+        // entities that have left the cosmos are clamped to its edge, this way they
+        // still end up in the outermost nodes and can collide with each other
+        let bounds = clamp(polygon.bounds_meld.new, self.initial);
 
-        self.root.add(eidx, bounds, entities);
-    }
+        // start at the bottom and move up until the bounds fit in a single node,
+        // the root always fits
+        let (level, x, y) = (0..=self.depth)
+            .rev()
+            .map(|level| {
+                let (x1, y1) = self.cell(level, bounds.x, bounds.y);
+                let (x2, y2) = self.cell(level, bounds.x + bounds.width, bounds.y + bounds.height);
+                (level, x1, y1, x2, y2)
+            })
+            .find(|(_, x1, y1, x2, y2)| x1 == x2 && y1 == y2)
+            .map(|(level, x, y, _, _)| (level, x, y))
+            .unwrap_or((0, 0, 0));
 
-    pub fn get(&self, area: &Rectangle, entities: &Entities) -> HashSet<EntityIndex> {
-        let mut v = HashSet::new();
+        let node = index(level, x, y);
+        let entry = Entry {
+            eid,
+            eidx,
+            bounds,
+            level,
+            x,
+            y,
+        };
 
-        // get potential entities
-        self.root.get(area, &mut v);
+        if self.locations.len() <= eid.slot() {
+            self.locations.resize(eid.slot() + 1, None);
+        }
+
+        if let Some(location) = self.locations[eid.slot()] {
+            // still in the same node, the bounds and the index may have changed though
+            if location.eid == eid && location.node == node {
+                self.nodes[node as usize][location.position as usize] = entry;
+                return;
+            }
+
+            // moved out of its node, or the slot belongs to something that's dead by now
+            self.remove(location);
+        }
+
+        let entries = &mut self.nodes[node as usize];
+
+        if entries.is_empty() {
+            self.used.push(node);
+        }
 
-        // remove entities not included within the area
-        v.retain(|x| bounds(*x, entities).check_collision_recs(area));
+        self.locations[eid.slot()] = Some(Location {
+            eid,
+            node,
+            position: entries.len() as u32,
+        });
 
-        v
+        entries.push(entry);
     }
 
-    pub fn draw(&self, r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>) {
-        draw_node(&self.root, r);
+    // takes out everything that's dead or shouldn't be in here anymore
+    pub fn retain(&mut self, mut keep: impl FnMut(Eid) -> bool) {
+        // backwards, whatever gets swapped into a removed spot has been looked at already
+        for used in (0..self.used.len()).rev() {
+            let node = self.used[used];
 
-        fn draw_node(node: &Node, r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>) {
-            match &node.node_type {
-                NodeType::Leaf(_) => {
-                    r.draw_rectangle_lines_ex(node.dimension, 1.0, Color::GREEN);
-                }
-                NodeType::Branch(nodes) => {
-                    for node in nodes {
-                        draw_node(&node, r);
-                    }
+            for position in (0..self.nodes[node as usize].len()).rev() {
+                let eid = self.nodes[node as usize][position].eid;
+
+                if !keep(eid) {
+                    self.remove(Location {
+                        eid,
+                        node,
+                        position: position as u32,
+                    });
                 }
-            };
+            }
         }
     }
-}
 
-fn bounds(eidx: EntityIndex, entities: &Entities) -> Rectangle {
-    match eidx {
-        EntityIndex::Triship(idx) => &entities.triships[idx].entity.body.polygon,
-        EntityIndex::Projectile(idx) => &entities.projectiles[idx].entity.body.polygon,
-        EntityIndex::Torpedo(idx) => &entities.torpedoes[idx].entity.body.polygon,
-        _ => panic!("bounds {:?}", eidx),
-    }
-    .bounds_meld
-    .new
-}
+    // everything whose bounds touch the area, the caller hands in the vector to keep reusing it
+    pub fn get(&self, area: &Rectangle, found: &mut Vec<EntityIndex>) {
+        found.clear();
 
-impl Node {
-    fn add(&mut self, eidx: EntityIndex, bounds: Rectangle, entities: &Entities) {
-        match &mut self.node_type {
-            NodeType::Leaf(ents) => {
-                if !self.dimension.check_collision_recs(&bounds) {
-                    return;
-                }
+        // get potential entities, only the nodes touching the area on every level
+        for level in 0..=self.depth {
+            let (x1, y1) = self.cell(level, area.x, area.y);
+            let (x2, y2) = self.cell(level, area.x + area.width, area.y + area.height);
 
-                if self.depth != MAX_DEPTH && ents.len() == MAX_SIZE {
-                    self.divide(entities);
-                    self.add(eidx, bounds, entities);
+            for y in y1..=y2 {
+                for x in x1..=x2 {
+                    let node = index(level, x, y) as usize;
 
-                    return;
+                    found.extend(
+                        self.nodes[node]
+                            .iter()
+                            .filter(|x| x.bounds.check_collision_recs(area))
+                            .map(|x| x.eidx),
+                    );
                 }
-
-                ents.push(eidx);
             }
-            NodeType::Branch(nodes) => {
-                for node in nodes {
-                    node.add(eidx, bounds, entities);
-                }
+        }
+
+        // the order must not depend on how the tree happened to be built,
+        // otherwise clients go out of sync
+        found.sort_unstable();
+    }
+
+    // every pair of entities whose bounds overlap, each pair shows up exactly once
+    pub fn pairs(&mut self, mut f: impl FnMut(EntityIndex, EntityIndex)) {
+        self.pairs.clear();
+
+        // lower index goes first
+        let mut pair = |one: &Entry, two: &Entry| {
+            if one.bounds.check_collision_recs(&two.bounds) {
+                self.pairs
+                    .push((one.eidx.min(two.eidx), one.eidx.max(two.eidx)));
             }
         };
-    }
 
-    fn get(&self, area: &Rectangle, entities: &mut HashSet<EntityIndex>) {
-        match &self.node_type {
-            NodeType::Leaf(ents) => {
-                if self.dimension.check_collision_recs(&area) {
-                    for ent in ents {
-                        entities.insert(*ent);
+        for node in self.used.iter() {
+            let entries = &self.nodes[*node as usize];
+
+            for (i, entry) in entries.iter().enumerate() {
+                // only look at the ones after us in the same node so we don't see a pair twice
+                for other in entries[i + 1..].iter() {
+                    pair(entry, other);
+                }
+
+                // everything in the nodes above could overlap as well, nodes next to
+                // each other can't since the bounds fit within them
+                for level in (0..entry.level).rev() {
+                    let shift = entry.level - level;
+                    let node = index(level, entry.x >> shift, entry.y >> shift) as usize;
+
+                    for other in self.nodes[node].iter() {
+                        pair(entry, other);
                     }
                 }
             }
-            NodeType::Branch(nodes) => {
-                for node in nodes {
-                    node.get(area, entities);
-                }
+        }
+
+        // the same goes for the pairs, the first one reacting could change the next one
+        self.pairs.sort_unstable();
+
+        for (eidx1, eidx2) in self.pairs.iter() {
+            f(*eidx1, *eidx2);
+        }
+    }
+
+    pub fn draw(&self, r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>) {
+        for node in self.used.iter() {
+            for entry in self.nodes[*node as usize].iter() {
+                let size = (1 << entry.level) as f32;
+                let width = self.initial.width / size;
+                let height = self.initial.height / size;
+
+                r.draw_rectangle_lines_ex(
+                    Rectangle {
+                        x: self.initial.x + entry.x as f32 * width,
+                        y: self.initial.y + entry.y as f32 * height,
+                        width,
+                        height,
+                    },
+                    1.0,
+                    Color::GREEN,
+                );
             }
         }
     }
 
-    fn divide(&mut self, entities: &Entities) {
-        let ents = match &self.node_type {
-            NodeType::Leaf(ents) => ents,
-            NodeType::Branch(_) => return,
-        };
+    fn remove(&mut self, location: Location) {
+        let node = location.node as usize;
+        let position = location.position as usize;
+        let entries = &mut self.nodes[node];
+
+        let removed = entries.swap_remove(position);
+        self.locations[removed.eid.slot()] = None;
+
+        // the last one took its place
+        if let Some(moved) = entries.get(position) {
+            self.locations[moved.eid.slot()] = Some(Location {
+                eid: moved.eid,
+                ..location
+            });
+        }
 
-        let x = self.dimension.x;
-        let y = self.dimension.y;
-        let width = self.dimension.width / 2.0;
-        let height = self.dimension.height / 2.0;
-        let depth = self.depth + 1;
-        let mut nodes = [
-            Box::new(Node {
-                node_type: NodeType::Leaf(Vec::new()),
-                dimension: Rectangle {
-                    x,
-                    y,
-                    width,
-                    height,
-                },
-                depth,
-            }),
-            Box::new(Node {
-                node_type: NodeType::Leaf(Vec::new()),
-                dimension: Rectangle {
-                    x: x + width,
-                    y,
-                    width,
-                    height,
-                },
-                depth,
-            }),
-            Box::new(Node {
-                node_type: NodeType::Leaf(Vec::new()),
-                dimension: Rectangle {
-                    x: x + width,
-                    y: y + height,
-                    width,
-                    height,
-                },
-                depth,
-            }),
-            Box::new(Node {
-                node_type: NodeType::Leaf(Vec::new()),
-                dimension: Rectangle {
-                    x,
-                    y: y + height,
-                    width,
-                    height,
-                },
-                depth,
-            }),
-        ];
-
-        for eidx in ents {
-            let bounds = bounds(*eidx, entities);
-
-            for node in nodes.iter_mut() {
-                node.add(*eidx, bounds, entities);
+        if entries.is_empty() {
+            if let Some(used) = self.used.iter().position(|x| *x == location.node) {
+                self.used.swap_remove(used);
             }
         }
+    }
+
+    fn cell(&self, level: u32, x: f32, y: f32) -> (u32, u32) {
+        let size = (1 << level) as f32;
+        let max = size - 1.0;
+
+        let x = ((x - self.initial.x) / self.initial.width * size).floor();
+        let y = ((y - self.initial.y) / self.initial.height * size).floor();
+
+        (x.clamp(0.0, max) as u32, y.clamp(0.0, max) as u32)
+    }
+}
+
+// all the nodes of the levels above come first
+fn offset(level: u32) -> u32 {
+    ((1 << (2 * level)) - 1) / 3
+}
+
+fn index(level: u32, x: u32, y: u32) -> u32 {
+    offset(level) + y * (1 << level) + x
+}
+
+fn clamp(bounds: Rectangle, area: Rectangle) -> Rectangle {
+    // keep at least 1.0 inside the area, rectangles only touching the edge don't collide
+    let min_x = bounds.x.clamp(area.x, area.x + area.width - 1.0);
+    let min_y = bounds.y.clamp(area.y, area.y + area.height - 1.0);
+    let max_x = (bounds.x + bounds.width).clamp(min_x + 1.0, area.x + area.width);
+    let max_y = (bounds.y + bounds.height).clamp(min_y + 1.0, area.y + area.height);
 
-        self.node_type = NodeType::Branch(nodes);
+    Rectangle {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x,
+        height: max_y - min_y,
     }
 }
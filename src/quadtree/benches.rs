@@ -0,0 +1,116 @@
+use std::time::Instant;
+
+use super::*;
+use crate::{
+    entities::Entity, forge::Forge, logic::harness::Seeded, random::Random, settings::Physics,
+};
+
+// not really tests, run them with `cargo test --release -- --ignored --nocapture bench`
+const WIDTH: i32 = 20000;
+const HEIGHT: i32 = 20000;
+const TICKS: u32 = 100;
+
+// triships and projectiles scattered all over the cosmos, same layout every time
+fn scatter(amount: usize) -> (Entities, Vec<Eid>) {
+    let forge = Forge::new();
+    let mut entities = Entities::new();
+    let mut seeded = Seeded::new(0x2545f491);
+    let mut random = move |max: i32| seeded.random(0..max - 1) as f32;
+
+    let ids = (0..amount)
+        .map(|i| {
+            let position = Vector2::new(random(WIDTH), random(HEIGHT));
+
+            if i % 4 == 0 {
+                let triship = forge.triship(position, 0, 0, Physics::Arcade);
+                entities.add(Entity::Triship(triship))
+            } else {
+                let direction = Vector2::new(random(200) - 100.0, random(200) - 100.0);
+                let projectile = forge.projectile(
+                    position,
+                    direction.normalized(),
+                    Vector2::zero(),
+                    Eid::NONE,
+                    1.0,
+                );
+                entities.add(Entity::Projectile(projectile))
+            }
+        })
+        .collect();
+
+    (entities, ids)
+}
+
+fn bench(amount: usize) {
+    let (mut entities, ids) = scatter(amount);
+    let mut quadtree = QuadTree::new(WIDTH, HEIGHT);
+
+    let viewport = Rectangle::new(9000.0, 9000.0, 1150.0, 720.0);
+    let minimap = Rectangle::new(4000.0, 6000.0, 11500.0, 7200.0);
+
+    let mut pairs = 0;
+    let mut found = 0;
+
+    let mut found_area = Vec::new();
+
+    let start = Instant::now();
+    ids.iter().for_each(|x| quadtree.add(*x, &entities));
+    let build = start.elapsed().as_nanos();
+
+    let mut update = 0;
+    let mut query = 0;
+    let mut get = 0;
+
+    for tick in 0..TICKS {
+        // everything drifts a little, now and then across the edge of its node
+        let drift = Vector2::new(8.0, (tick % 3) as f32 * 4.0 - 4.0);
+
+        entities.each_mut(|_, x| {
+            if let Some(body) = x.body {
+                body.generation();
+                body.accelerate(drift);
+                body.renew();
+            }
+        });
+
+        let start = Instant::now();
+        quadtree.retain(|x| entities.entity(x).is_some());
+        ids.iter().for_each(|x| quadtree.add(*x, &entities));
+        update += start.elapsed().as_nanos();
+
+        let start = Instant::now();
+        quadtree.pairs(|_, _| pairs += 1);
+        query += start.elapsed().as_nanos();
+
+        let start = Instant::now();
+        quadtree.get(&viewport, &mut found_area);
+        found += found_area.len();
+        quadtree.get(&minimap, &mut found_area);
+        found += found_area.len();
+        get += start.elapsed().as_nanos();
+    }
+
+    let ticks = TICKS as u128;
+    println!(
+        "{} entities: build {}us, update {}us, pairs {}us ({}), get {}us ({})",
+        amount,
+        build / 1000,
+        update / ticks / 1000,
+        query / ticks / 1000,
+        pairs / TICKS,
+        get / ticks / 1000,
+        found / TICKS as usize,
+    );
+}
+
+#[test]
+#[ignore]
+fn bench_1k() {
+    bench(1000);
+}
+
+#[test]
+#[ignore]
+fn bench_10k() {
+    bench(10000);
+}
@@ -0,0 +1,15 @@
+use std::ops::Range;
+
+use raylib::prelude::*;
+
+// the simulation only needs random numbers from raylib, keeping them behind a trait
+// lets it run without a window, e.g. in tests or on a headless server
+pub trait Random {
+    fn random(&mut self, range: Range<i32>) -> i32;
+}
+
+impl Random for RaylibHandle {
+    fn random(&mut self, range: Range<i32>) -> i32 {
+        self.get_random_value::<i32>(range)
+    }
+}
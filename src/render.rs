@@ -3,9 +3,13 @@ use std::ops::Add;
 use raylib::prelude::*;
 
 use crate::{
-    components::{Centroidable, Cullable, Lerpable, Triangle},
-    constants::{COSMOS_HEIGHT, COSMOS_WIDTH, STARFIELD_HEIGHT, STARFIELD_WIDTH},
-    entities::{Entities, EntityIndex},
+    components::{Centroidable, Cullable, Lerpable, Triangle, Vertexable},
+    constants::{
+        FLARE_LIFETIME, INDICATOR_MARGIN, INDICATOR_SIZE, STARFIELD_HEIGHT, STARFIELD_WIDTH,
+        TEAM_NONE,
+    },
+    entities::{Entities, EntityIndex, PickupKind, WellKind},
+    maps::Map,
     math::*,
     states::play::RenderData,
     utils::generate_targeting_area,
@@ -23,19 +27,154 @@ impl Renderer {
         r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
         entities: &Entities,
         data: &RenderData,
+        map: &Map,
         viewport: Rectangle,
         debug: bool,
         delta: f32,
     ) {
+        let (red, green, blue) = map.background.color;
+        r.draw_rectangle(
+            0,
+            0,
+            map.width,
+            map.height,
+            Color::new(red, green, blue, 255),
+        );
+
         if debug {
             r.draw_rectangle_lines_ex(viewport, 1.0, Color::RED);
         }
 
-        r.draw_rectangle_lines(0, 0, COSMOS_WIDTH, COSMOS_HEIGHT, Color::RED);
+        r.draw_rectangle_lines(0, 0, map.width, map.height, Color::RED);
 
-        draw_entities(r, entities, viewport, debug, delta);
+        draw_entities(r, entities, map, viewport, debug, delta);
         draw_visuals(r, entities, data, viewport, debug, delta);
+        draw_indicators(r, entities, data, viewport, delta);
+    }
+}
+
+// arrows on the viewport edge pointing to the target, team-mates and objectives off screen
+fn draw_indicators(
+    r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
+    entities: &Entities,
+    data: &RenderData,
+    viewport: Rectangle,
+    delta: f32,
+) {
+    let Some(player) = data.player_entity_id.and_then(|x| entities.triship(x)) else {
+        return;
+    };
+
+    let team = player.team;
+
+    let target = data.target.and_then(|x| entities.entity(x));
+    let target = target.and_then(|eidx| match eidx {
+        EntityIndex::Triship(idx) => Some(
+            entities.triships[idx]
+                .entity
+                .body
+                .polygon
+                .bounds_real
+                .lerp(delta),
+        ),
+        EntityIndex::Torpedo(idx) => Some(
+            entities.torpedoes[idx]
+                .entity
+                .body
+                .polygon
+                .bounds_real
+                .lerp(delta),
+        ),
+        EntityIndex::Flag(idx) => Some(
+            entities.flags[idx]
+                .entity
+                .body
+                .polygon
+                .bounds_real
+                .lerp(delta),
+        ),
+        _ => None,
+    });
+
+    // the target gets its own color, don't point at it twice
+    let others = entities
+        .triships
+        .iter()
+        .filter(|x| {
+            team != TEAM_NONE && x.entity.team == team && Some(x.id) != data.player_entity_id
+        })
+        .map(|x| {
+            (
+                x.id,
+                &x.entity.body.polygon.bounds_real,
+                x.entity.body.color,
+            )
+        })
+        .chain(entities.flags.iter().map(|x| {
+            (
+                x.id,
+                &x.entity.body.polygon.bounds_real,
+                x.entity.body.color,
+            )
+        }))
+        .chain(entities.bases.iter().map(|x| {
+            (
+                x.id,
+                &x.entity.body.polygon.bounds_real,
+                x.entity.body.color,
+            )
+        }))
+        .filter(|(eid, _, _)| Some(*eid) != data.target)
+        .map(|(_, bounds, color)| (bounds.lerp(delta), color));
+
+    let indicators = target.map(|x| (x, Color::RED)).into_iter().chain(others);
+
+    for (bounds, color) in indicators {
+        let Some((point, direction)) = draw_arrow(r, bounds, viewport, color) else {
+            continue;
+        };
+
+        let text = format!("{}", direction.length() as i32);
+        let len = r.measure_text(&text, 10);
+        let xy = point - direction.normalized() * INDICATOR_SIZE * 3.0;
+
+        r.draw_text(&text, xy.x as i32 - len / 2, xy.y as i32 - 5, 10, color);
+    }
+}
+
+// an arrow on the viewport edge pointing at bounds off screen, returns where it went and how far
+pub fn draw_arrow(
+    r: &mut impl RaylibDraw,
+    bounds: Rectangle,
+    viewport: Rectangle,
+    color: Color,
+) -> Option<(Vector2, Vector2)> {
+    if !bounds.cull(viewport) {
+        return None;
     }
+
+    let center = Vector2::new(
+        viewport.x + viewport.width / 2.0,
+        viewport.y + viewport.height / 2.0,
+    );
+    let direction = bounds.centroid() - center;
+
+    // scale the direction so it ends on the viewport edge
+    let scale = ((viewport.width / 2.0 - INDICATOR_MARGIN) / direction.x.abs())
+        .min((viewport.height / 2.0 - INDICATOR_MARGIN) / direction.y.abs());
+    let point = center + direction * scale;
+
+    let n = direction.normalized();
+    let p = Vector2::new(-n.y, n.x);
+
+    r.draw_triangle(
+        point + n * INDICATOR_SIZE,
+        point - n * INDICATOR_SIZE - p * INDICATOR_SIZE,
+        point - n * INDICATOR_SIZE + p * INDICATOR_SIZE,
+        color,
+    );
+
+    Some((point, direction))
 }
 
 fn draw_visuals(
@@ -46,28 +185,36 @@ fn draw_visuals(
     debug: bool,
     delta: f32,
 ) {
-    // draw line towards the target and circle around target
-    if let (Some(eidx_target), Some(eidx)) = (data.target_eidx, data.player_eidx) {
-        let centroid_target = match eidx_target {
-            EntityIndex::Triship(idx) => entities.triships[idx]
+    let player = data.player_entity_id.and_then(|x| entities.triship(x));
+    let target = data.target.and_then(|x| entities.entity(x));
+
+    let centroid_target = target.and_then(|eidx_target| match eidx_target {
+        EntityIndex::Triship(idx) => Some(
+            entities.triships[idx]
                 .entity
                 .body
                 .state
                 .lerp(delta)
                 .centroid(),
-            _ => panic!("wtf centroid target {:?}", eidx_target),
-        };
+        ),
+        EntityIndex::Torpedo(idx) => Some(
+            entities.torpedoes[idx]
+                .entity
+                .body
+                .state
+                .lerp(delta)
+                .centroid(),
+        ),
+        EntityIndex::Flag(idx) => {
+            Some(entities.flags[idx].entity.body.state.lerp(delta).centroid())
+        }
+        _ => None,
+    });
 
-        let (centroid, timer) = match eidx {
-            EntityIndex::Triship(idx) => {
-                let e = &entities.triships[idx].entity;
-                (
-                    e.body.state.lerp(delta).centroid(),
-                    e.targeting.visual.current,
-                )
-            }
-            _ => panic!("wtf centroid {:?}", eidx_target),
-        };
+    // draw line towards the target and circle around target
+    if let (Some(centroid_target), Some(e)) = (centroid_target, player) {
+        let centroid = e.body.state.lerp(delta).centroid();
+        let timer = e.targeting.visual.current;
 
         r.draw_circle_lines(
             centroid_target.x as i32,
@@ -92,16 +239,8 @@ fn draw_visuals(
     }
 
     if debug {
-        if let Some(eidx) = data.player_eidx {
-            let centroid = match eidx {
-                EntityIndex::Triship(idx) => entities.triships[idx]
-                    .entity
-                    .body
-                    .state
-                    .lerp(delta)
-                    .centroid(),
-                _ => panic!("wtf draw targeting area {:?}", eidx),
-            };
+        if let Some(e) = player {
+            let centroid = e.body.state.lerp(delta).centroid();
 
             let targeting_area = generate_targeting_area(centroid);
 
@@ -113,21 +252,32 @@ fn draw_visuals(
 fn draw_entities(
     r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
     entities: &Entities,
+    map: &Map,
     viewport: Rectangle,
     debug: bool,
     delta: f32,
 ) {
-    draw_stars(r, entities, viewport, delta);
+    draw_stars(r, entities, map, viewport, delta);
+    draw_obstacles(r, entities, viewport);
+    draw_bases(r, entities, viewport);
+    draw_wells(r, entities, viewport);
+    draw_asteroids(r, entities, viewport, delta);
+    draw_pickups(r, entities, viewport);
+    draw_mines(r, entities, viewport, delta);
     draw_exhausts(r, entities, viewport, delta);
+    draw_flares(r, entities, viewport, delta);
     draw_triships(r, entities, viewport, debug, delta);
+    draw_flags(r, entities, viewport, delta);
     draw_explosions(r, entities, viewport, delta);
     draw_projectiles(r, entities, viewport, delta);
     draw_torpedoes(r, entities, viewport, debug, delta);
+    draw_beams(r, entities, viewport);
 }
 
 fn draw_stars(
     r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
     entities: &Entities,
+    map: &Map,
     viewport: Rectangle,
     delta: f32,
 ) {
@@ -138,15 +288,15 @@ fn draw_stars(
         let min_x = if viewport.x < 0.0 {
             0.0
         } else {
-            if viewport.x > COSMOS_WIDTH as f32 {
-                COSMOS_WIDTH as f32
+            if viewport.x > map.width as f32 {
+                map.width as f32
             } else {
                 viewport.x
             }
         };
 
-        let max_x = if viewport.x + viewport.width > COSMOS_WIDTH as f32 {
-            COSMOS_WIDTH as f32
+        let max_x = if viewport.x + viewport.width > map.width as f32 {
+            map.width as f32
         } else {
             viewport.x + viewport.width
         };
@@ -154,15 +304,15 @@ fn draw_stars(
         let min_y = if viewport.y < 0.0 {
             0.0
         } else {
-            if viewport.y > COSMOS_HEIGHT as f32 {
-                COSMOS_HEIGHT as f32
+            if viewport.y > map.height as f32 {
+                map.height as f32
             } else {
                 viewport.y
             }
         };
 
-        let max_y = if viewport.y + viewport.height > COSMOS_HEIGHT as f32 {
-            COSMOS_HEIGHT as f32
+        let max_y = if viewport.y + viewport.height > map.height as f32 {
+            map.height as f32
         } else {
             viewport.y + viewport.height
         };
@@ -206,6 +356,182 @@ fn draw_stars(
     }
 }
 
+fn draw_obstacles(
+    r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
+    entities: &Entities,
+    viewport: Rectangle,
+) {
+    for obstacle in &entities.obstacles {
+        // obstacles never move, no need to interpolate
+        let polygon = &obstacle.entity.polygon;
+
+        if polygon.bounds_real.new.cull(viewport) {
+            continue;
+        }
+
+        let v = &polygon.vertexes.new;
+        for i in 0..v.len() {
+            r.draw_line_v(v[i], v[(i + 1) % v.len()], obstacle.entity.color);
+        }
+    }
+}
+
+fn draw_wells(
+    r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
+    entities: &Entities,
+    viewport: Rectangle,
+) {
+    for well in &entities.wells {
+        // wells never move either, but their pull reaches way past the core
+        let e = &well.entity;
+        let center = e.body.state.new.shape.center;
+        let bounds = Rectangle {
+            x: center.x - e.range,
+            y: center.y - e.range,
+            width: e.range * 2.0,
+            height: e.range * 2.0,
+        };
+
+        if bounds.cull(viewport) {
+            continue;
+        }
+
+        r.draw_circle_lines(
+            center.x as i32,
+            center.y as i32,
+            e.range,
+            e.body.color.alpha(0.2),
+        );
+
+        match e.kind {
+            WellKind::Planet => {
+                r.draw_circle_v(center, e.radius, e.body.color);
+            }
+            WellKind::BlackHole => {
+                r.draw_circle_v(center, e.radius, Color::BLACK);
+                r.draw_circle_lines(center.x as i32, center.y as i32, e.radius, e.body.color);
+            }
+        }
+    }
+}
+
+fn draw_bases(
+    r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
+    entities: &Entities,
+    viewport: Rectangle,
+) {
+    for base in &entities.bases {
+        // bases never move, no need to interpolate
+        let bounds = base.entity.body.polygon.bounds_real.new;
+
+        if bounds.cull(viewport) {
+            continue;
+        }
+
+        r.draw_rectangle_lines_ex(bounds, 2.0, base.entity.body.color);
+        r.draw_text(
+            &format!("{}", base.entity.score),
+            (bounds.x + 8.0) as i32,
+            (bounds.y + 8.0) as i32,
+            20,
+            base.entity.body.color,
+        );
+    }
+}
+
+fn draw_asteroids(
+    r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
+    entities: &Entities,
+    viewport: Rectangle,
+    delta: f32,
+) {
+    for asteroid in &entities.asteroids {
+        let bounds = asteroid.entity.body.polygon.bounds_real.lerp(delta);
+
+        if bounds.cull(viewport) {
+            continue;
+        }
+
+        let gen = &asteroid.entity.body.state;
+        let rot = gen.old.rotation.lerp(gen.new.rotation, delta);
+        let vertexes = gen.lerp(delta).vertexes(rot);
+
+        for i in 0..vertexes.len() {
+            let v1 = vertexes[i];
+            let v2 = vertexes[(i + 1) % vertexes.len()];
+
+            r.draw_line_v(v1, v2, asteroid.entity.body.color);
+        }
+    }
+}
+
+fn draw_pickups(
+    r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
+    entities: &Entities,
+    viewport: Rectangle,
+) {
+    for pickup in &entities.pickups {
+        // already collected, waiting to respawn
+        if pickup.entity.respawn.current != 0 {
+            continue;
+        }
+
+        // pickups never move, no need to interpolate
+        let bounds = pickup.entity.body.polygon.bounds_real.new;
+
+        if bounds.cull(viewport) {
+            continue;
+        }
+
+        r.draw_rectangle_lines_ex(bounds, 2.0, pickup.entity.body.color);
+        r.draw_text(
+            match pickup.entity.kind {
+                PickupKind::Health => "H",
+                PickupKind::Boost => "B",
+                PickupKind::Ammo => "A",
+                PickupKind::RapidFire => "R",
+            },
+            (bounds.x + 7.0) as i32,
+            (bounds.y + 5.0) as i32,
+            10,
+            pickup.entity.body.color,
+        );
+    }
+}
+
+fn draw_flags(
+    r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
+    entities: &Entities,
+    viewport: Rectangle,
+    delta: f32,
+) {
+    for flag in &entities.flags {
+        let bounds = flag.entity.body.polygon.bounds_real.lerp(delta);
+
+        if bounds.cull(viewport) {
+            continue;
+        }
+
+        let ent = flag.entity.body.state.lerp(delta);
+
+        // pole and cloth
+        r.draw_line_v(
+            Vector2::new(ent.x, ent.y),
+            Vector2::new(ent.x, ent.y + ent.height),
+            flag.entity.body.color,
+        );
+        r.draw_rectangle_rec(
+            Rectangle {
+                x: ent.x,
+                y: ent.y,
+                width: ent.width,
+                height: ent.height / 2.0,
+            },
+            flag.entity.body.color,
+        );
+    }
+}
+
 fn draw_projectiles(
     r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
     entities: &Entities,
@@ -240,6 +566,71 @@ fn draw_projectiles(
     }
 }
 
+fn draw_mines(
+    r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
+    entities: &Entities,
+    viewport: Rectangle,
+    delta: f32,
+) {
+    for mine in &entities.mines {
+        let bounds = mine.entity.body.polygon.bounds_real.lerp(delta);
+
+        if bounds.cull(viewport) {
+            continue;
+        }
+
+        let gen = mine.entity.body.state;
+        let rot = gen.old.rotation.lerp(gen.new.rotation, delta);
+        let rad = rot.y.atan2(rot.x);
+        let ent = gen.lerp(delta);
+
+        // dim until armed
+        let color = if mine.entity.timer_inactive != 0 {
+            Color::GRAY
+        } else {
+            mine.entity.body.color
+        };
+
+        // for some reason we need to add half the width and height to rotated rectangle's x and y
+        r.draw_rectangle_pro(
+            Rectangle {
+                x: ent.x + ent.width / 2.0,
+                y: ent.y + ent.height / 2.0,
+                width: ent.width,
+                height: ent.height,
+            },
+            Vector2::new(ent.width / 2.0, ent.height / 2.0),
+            rad.to_degrees(),
+            color,
+        );
+    }
+}
+
+fn draw_beams(
+    r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
+    entities: &Entities,
+    viewport: Rectangle,
+) {
+    for beam in &entities.beams {
+        let b = &beam.entity;
+        let bounds = Rectangle {
+            x: b.origin.x.min(b.end.x),
+            y: b.origin.y.min(b.end.y),
+            width: (b.origin.x - b.end.x).abs(),
+            height: (b.origin.y - b.end.y).abs(),
+        };
+
+        if bounds.cull(viewport) {
+            continue;
+        }
+
+        // fade out as it dies down
+        let alpha = b.lifetime.current as f32 / b.lifetime.max as f32;
+
+        r.draw_line_ex(b.origin, b.end, 2.0, b.color.alpha(alpha));
+    }
+}
+
 fn draw_torpedoes(
     r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
     entities: &Entities,
@@ -324,6 +715,28 @@ fn draw_exhausts(
     }
 }
 
+fn draw_flares(
+    r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
+    entities: &Entities,
+    viewport: Rectangle,
+    delta: f32,
+) {
+    for flare in &entities.flares {
+        let bounds = flare.entity.body.polygon.bounds_real.lerp(delta);
+
+        if bounds.cull(viewport) {
+            continue;
+        }
+
+        let ent = flare.entity.body.state.lerp(delta);
+
+        // burn out towards the end
+        let alpha = flare.entity.lifetime as f32 / FLARE_LIFETIME as f32;
+
+        r.draw_circle_v(ent, 3.0, flare.entity.body.color.alpha(alpha));
+    }
+}
+
 fn draw_triships(
     r: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>,
     entities: &Entities,
@@ -352,6 +765,31 @@ fn draw_triships(
 
         r.draw_triangle_lines(ent.v1, ent.v2, ent.v3, triship.entity.body.color);
 
+        // flash the shield bubble on impact, or the hull edge that took the hit if it got through
+        let shield = &triship.entity.shield;
+        if shield.flash.current != 0 {
+            let alpha = shield.flash.current as f32 / shield.flash.max as f32;
+            let vertexes = [ent.v1, ent.v2, ent.v3];
+            let radius = vertexes
+                .iter()
+                .map(|x| x.distance_to(ori))
+                .fold(0.0, f32::max);
+
+            if shield.breached {
+                let v1 = vertexes[shield.edge % vertexes.len()];
+                let v2 = vertexes[(shield.edge + 1) % vertexes.len()];
+
+                r.draw_line_ex(v1, v2, 2.0, Color::ORANGE.alpha(alpha));
+            } else {
+                r.draw_circle_lines(
+                    ori.x as i32,
+                    ori.y as i32,
+                    radius + 6.0,
+                    Color::SKYBLUE.alpha(alpha),
+                );
+            }
+        }
+
         if !debug {
             continue;
         }
@@ -0,0 +1,303 @@
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Settings {
+    pub mode: Mode,
+    pub boundary: Boundary,
+    pub physics: Physics,
+    pub bots: u8,
+    pub difficulty: Difficulty,
+    pub ships: u32,
+    pub map: String,
+    pub map_hash: u32,
+    pub classes: Vec<(u32, u8)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Mode {
+    Deathmatch,
+    CaptureTheFlag,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Boundary {
+    Wrap,
+    Damage,
+    Repel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Physics {
+    Arcade,
+    Newtonian,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Self {
+            mode: Mode::Deathmatch,
+            boundary: Boundary::Repel,
+            physics: Physics::Arcade,
+            bots: 0,
+            difficulty: Difficulty::Normal,
+            ships: 0,
+            map: String::new(),
+            map_hash: 0,
+            classes: Vec::new(),
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        // length will be the first two bytes, don't care about it in here
+        let Some((mode, data)) = bytes[2..].split_first() else {
+            panic!("wtf settings");
+        };
+
+        let Some((boundary, data)) = data.split_first() else {
+            panic!("wtf settings boundary");
+        };
+
+        let Some((physics, data)) = data.split_first() else {
+            panic!("wtf settings physics");
+        };
+
+        let Some((bots, data)) = data.split_first() else {
+            panic!("wtf settings bots");
+        };
+
+        let Some((difficulty, data)) = data.split_first() else {
+            panic!("wtf settings difficulty");
+        };
+
+        let (ships, data) = data.split_at(4);
+        let ships = u32::from_be_bytes(ships.try_into().expect("wtf settings ships"));
+
+        let (map_hash, data) = data.split_at(4);
+        let map_hash = u32::from_be_bytes(map_hash.try_into().expect("wtf settings map hash"));
+
+        let (map_len, data) = data.split_at(2);
+        let map_len = u16::from_be_bytes(map_len.try_into().expect("wtf settings map"));
+
+        let (map, data) = data.split_at(map_len as usize);
+        let map = String::from_utf8(map.to_vec()).expect("wtf settings map name");
+
+        let mut classes = Vec::new();
+        let mut read = 0;
+
+        while read < data.len() {
+            let cid = data[read..]
+                .first_chunk::<4>()
+                .expect("wtf settings classes");
+            let cid = u32::from_be_bytes(*cid);
+
+            read += 4;
+
+            let class = data[read];
+
+            read += 1;
+
+            classes.push((cid, class));
+        }
+
+        Self {
+            mode: Mode::from_byte(*mode),
+            boundary: Boundary::from_byte(*boundary),
+            physics: Physics::from_byte(*physics),
+            bots: *bots,
+            difficulty: Difficulty::from_byte(*difficulty),
+            ships,
+            map,
+            map_hash,
+            classes,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(&self.len().to_be_bytes());
+        bytes.push(self.mode.to_byte());
+        bytes.push(self.boundary.to_byte());
+        bytes.push(self.physics.to_byte());
+        bytes.push(self.bots);
+        bytes.push(self.difficulty.to_byte());
+        bytes.extend_from_slice(&self.ships.to_be_bytes());
+        bytes.extend_from_slice(&self.map_hash.to_be_bytes());
+        bytes.extend_from_slice(&(self.map.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(self.map.as_bytes());
+
+        for (cid, class) in self.classes.iter() {
+            bytes.extend_from_slice(&cid.to_be_bytes());
+            bytes.push(*class);
+        }
+
+        bytes.into_boxed_slice()
+    }
+
+    pub fn len(&self) -> u16 {
+        // length itself + mode + boundary + physics + bots + difficulty + ships hash + map hash
+        // + map name + client ids with their classes
+        let len = 2 + 1 + 1 + 1 + 1 + 1 + 4 + 4 + 2 + self.map.len() + self.classes.len() * (4 + 1);
+
+        // a map name or a lobby this big is a bug somewhere, better than a garbled sync
+        u16::try_from(len).expect("wtf settings too long")
+    }
+
+    pub fn class(&self, cid: u32) -> u8 {
+        match self.classes.iter().find(|(x, _)| *x == cid) {
+            Some((_, class)) => *class,
+            None => 0,
+        }
+    }
+}
+
+impl Mode {
+    const DEATHMATCH: u8 = 1;
+    const CAPTURE_THE_FLAG: u8 = 2;
+
+    pub fn next(self) -> Self {
+        match self {
+            Mode::Deathmatch => Mode::CaptureTheFlag,
+            Mode::CaptureTheFlag => Mode::Deathmatch,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Deathmatch => "deathmatch",
+            Mode::CaptureTheFlag => "capture the flag",
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            Self::DEATHMATCH => Mode::Deathmatch,
+            Self::CAPTURE_THE_FLAG => Mode::CaptureTheFlag,
+            _ => panic!("wtf mode {}", byte),
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Mode::Deathmatch => Self::DEATHMATCH,
+            Mode::CaptureTheFlag => Self::CAPTURE_THE_FLAG,
+        }
+    }
+}
+
+impl Boundary {
+    const WRAP: u8 = 1;
+    const DAMAGE: u8 = 2;
+    const REPEL: u8 = 3;
+
+    pub fn next(self) -> Self {
+        match self {
+            Boundary::Wrap => Boundary::Damage,
+            Boundary::Damage => Boundary::Repel,
+            Boundary::Repel => Boundary::Wrap,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Boundary::Wrap => "wrap",
+            Boundary::Damage => "damage",
+            Boundary::Repel => "repel",
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            Self::WRAP => Boundary::Wrap,
+            Self::DAMAGE => Boundary::Damage,
+            Self::REPEL => Boundary::Repel,
+            _ => panic!("wtf boundary {}", byte),
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Boundary::Wrap => Self::WRAP,
+            Boundary::Damage => Self::DAMAGE,
+            Boundary::Repel => Self::REPEL,
+        }
+    }
+}
+
+impl Physics {
+    const ARCADE: u8 = 1;
+    const NEWTONIAN: u8 = 2;
+
+    pub fn next(self) -> Self {
+        match self {
+            Physics::Arcade => Physics::Newtonian,
+            Physics::Newtonian => Physics::Arcade,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Physics::Arcade => "arcade",
+            Physics::Newtonian => "newtonian",
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            Self::ARCADE => Physics::Arcade,
+            Self::NEWTONIAN => Physics::Newtonian,
+            _ => panic!("wtf physics {}", byte),
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Physics::Arcade => Self::ARCADE,
+            Physics::Newtonian => Self::NEWTONIAN,
+        }
+    }
+}
+
+impl Difficulty {
+    const EASY: u8 = 1;
+    const NORMAL: u8 = 2;
+    const HARD: u8 = 3;
+
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            Self::EASY => Difficulty::Easy,
+            Self::NORMAL => Difficulty::Normal,
+            Self::HARD => Difficulty::Hard,
+            _ => panic!("wtf difficulty {}", byte),
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Difficulty::Easy => Self::EASY,
+            Difficulty::Normal => Self::NORMAL,
+            Difficulty::Hard => Self::HARD,
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use serde::Deserialize;
+
+use crate::{components::WeaponKind, constants::SHIPS_PATH, utils::hash};
+
+pub struct Ships {
+    pub weapons: Vec<WeaponClass>,
+    pub classes: Vec<ShipClass>,
+    pub hash: u32,
+}
+
+#[derive(Deserialize)]
+struct ShipsFile {
+    weapons: Vec<WeaponClass>,
+    classes: Vec<ShipClass>,
+}
+
+#[derive(Deserialize)]
+pub struct WeaponClass {
+    pub name: String,
+    pub kind: WeaponKind,
+    pub damage: f32,
+    pub cooldown: u8,
+    pub ammo: Option<u8>,
+    pub heat: u8,
+}
+
+#[derive(Deserialize)]
+pub struct ShipClass {
+    pub name: String,
+    pub life: f32,
+    pub shield: ShieldClass,
+    pub hull: Vec<(f32, f32)>,
+    pub motion: MotionClass,
+    pub boost: BoostClass,
+    pub loadout: LoadoutClass,
+    pub countermeasures: CountermeasuresClass,
+    pub targeting: TargetingClass,
+}
+
+#[derive(Deserialize)]
+pub struct ShieldClass {
+    pub capacity: f32,
+    pub regen: f32,
+    pub delay: u8,
+}
+
+#[derive(Deserialize)]
+pub struct MotionClass {
+    pub acceleration: f32,
+    pub speed_max: f32,
+    pub rotation_acceleration: f32,
+    pub rotation_speed_max: f32,
+}
+
+#[derive(Deserialize)]
+pub struct BoostClass {
+    pub acceleration: f32,
+    pub speed_max: f32,
+    pub lifetime: u8,
+    pub cooldown: u8,
+}
+
+#[derive(Deserialize)]
+pub struct LoadoutClass {
+    pub primary: Vec<String>,
+    pub secondary: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CountermeasuresClass {
+    pub ammo: u8,
+    pub cooldown: u8,
+}
+
+#[derive(Deserialize)]
+pub struct TargetingClass {
+    pub timer: u8,
+}
+
+impl Ships {
+    pub fn load() -> Self {
+        let data = match std::fs::read_to_string(SHIPS_PATH) {
+            Ok(data) => data,
+            Err(error) => panic!("wtf ships {}: {}", SHIPS_PATH, error),
+        };
+
+        let ShipsFile { weapons, classes } = match ron::from_str(&data) {
+            Ok(file) => file,
+            Err(error) => panic!("wtf ships {}: {}", SHIPS_PATH, error),
+        };
+
+        if classes.is_empty() {
+            panic!("wtf ships {}: no classes", SHIPS_PATH);
+        }
+
+        // triships are triangles, nothing else will do
+        if let Some(class) = classes.iter().find(|x| x.hull.len() != 3) {
+            panic!(
+                "wtf ships {}: {} hull needs 3 vertexes",
+                SHIPS_PATH, class.name
+            );
+        }
+
+        for class in classes.iter() {
+            let loadout = &class.loadout;
+
+            if let Some(name) = loadout
+                .primary
+                .iter()
+                .chain(loadout.secondary.iter())
+                .find(|x| !weapons.iter().any(|w| w.name == **x))
+            {
+                panic!(
+                    "wtf ships {}: {} unknown weapon {}",
+                    SHIPS_PATH, class.name, name
+                );
+            }
+        }
+
+        Self {
+            weapons,
+            classes,
+            // hash the raw file, everyone must agree on every single byte
+            hash: hash(data.as_bytes()),
+        }
+    }
+
+    pub fn class(&self, class: u8) -> &ShipClass {
+        &self.classes[class as usize % self.classes.len()]
+    }
+
+    pub fn weapon(&self, name: &str) -> &WeaponClass {
+        match self.weapons.iter().find(|x| x.name == name) {
+            Some(weapon) => weapon,
+            None => panic!("wtf weapon {}", name),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+pub mod editor;
 pub mod lobby;
 pub mod menu;
 pub mod play;
@@ -9,9 +10,10 @@ use crate::{
     messages::{Message, StateMessage, StateRequestMessage},
 };
 
+use editor::Editor;
 use lobby::Lobby;
 use menu::Menu;
-use play::Play;
+use play::{Play, Practice, Source};
 
 #[derive(Copy, Clone, Debug)]
 pub enum State {
@@ -19,6 +21,9 @@ pub enum State {
     Menu,
     Lobby,
     Play,
+    // offline with bots on a map of our choosing, no lobby and no network
+    Practice(Practice),
+    Editor,
 }
 
 enum Action {
@@ -29,6 +34,7 @@ struct States {
     menu: Menu,
     lobby: Lobby,
     play: Play,
+    editor: Editor,
 }
 
 pub struct System {
@@ -45,6 +51,7 @@ impl System {
                 play: Play::new(),
                 menu: Menu::new(),
                 lobby: Lobby::new(),
+                editor: Editor::new(),
             },
             actions: Vec::new(),
         }
@@ -55,27 +62,30 @@ impl System {
 
         match self.current {
             State::None => (),
-            State::Play => self.states.play.update(h, bus),
+            State::Play | State::Practice(_) => self.states.play.update(h, bus),
             State::Menu => self.states.menu.update(h, bus),
             State::Lobby => self.states.lobby.update(h, bus),
+            State::Editor => self.states.editor.update(h, bus),
         }
     }
 
     pub fn input(&mut self, h: &mut RaylibHandle) {
         match self.current {
             State::None => (),
-            State::Play => self.states.play.input(h),
+            State::Play | State::Practice(_) => self.states.play.input(h),
             State::Menu => self.states.menu.input(h),
             State::Lobby => self.states.lobby.input(h),
+            State::Editor => self.states.editor.input(h),
         }
     }
 
     pub fn draw(&mut self, r: &mut RaylibTextureMode<RaylibDrawHandle>, delta: f32) {
         match self.current {
             State::None => (),
-            State::Play => self.states.play.draw(r, delta),
+            State::Play | State::Practice(_) => self.states.play.draw(r, delta),
             State::Menu => self.states.menu.draw(r, delta),
             State::Lobby => self.states.lobby.draw(r, delta),
+            State::Editor => self.states.editor.draw(r, delta),
         }
     }
 
@@ -90,9 +100,10 @@ impl System {
 
         match self.current {
             State::None => (),
-            State::Play => self.states.play.message(msg),
+            State::Play | State::Practice(_) => self.states.play.message(msg),
             State::Menu => self.states.menu.message(msg),
             State::Lobby => self.states.lobby.message(msg),
+            State::Editor => self.states.editor.message(msg),
         }
     }
 
@@ -105,9 +116,10 @@ impl System {
                     // exit the old state
                     match self.current {
                         State::None => (),
-                        State::Play => self.states.play.exit(),
+                        State::Play | State::Practice(_) => self.states.play.exit(),
                         State::Menu => self.states.menu.exit(),
                         State::Lobby => self.states.lobby.exit(),
+                        State::Editor => self.states.editor.exit(),
                     }
 
                     self.current = state;
@@ -115,9 +127,13 @@ impl System {
                     // init the new state
                     match self.current {
                         State::None => (),
-                        State::Play => self.states.play.init(bus),
+                        State::Play => self.states.play.init(bus, Source::Network),
+                        State::Practice(practice) => {
+                            self.states.play.init(bus, Source::Loopback(practice))
+                        }
                         State::Menu => self.states.menu.init(bus),
                         State::Lobby => self.states.lobby.init(bus),
+                        State::Editor => self.states.editor.init(bus),
                     }
                 }
             }
@@ -0,0 +1,681 @@
+use std::collections::BTreeSet;
+
+use raylib::prelude::*;
+
+use crate::{
+    bus::Bus,
+    constants::{
+        DEBUG_COLOR, EDITOR_OBSTACLE_SIZE, EDITOR_PAN_SPEED, EDITOR_PICK_RADIUS, EDITOR_ZOOM_MAX,
+        EDITOR_ZOOM_MIN, HUD_BACKGROUND_COLOR, HUD_HEIGHT, HUD_SEPARATOR_COLOR, HUD_WIDTH, HUD_X,
+        HUD_Y, PRACTICE_BOTS, VIEWPORT_HEIGHT, VIEWPORT_WIDTH, WELL_BLACK_HOLE_RADIUS,
+        WELL_PLANET_RADIUS,
+    },
+    entities::{Entities, Entity, PickupKind, WellKind},
+    forge::Forge,
+    maps::{Map, Maps, Placement},
+    messages::{Message, StateRequestMessage},
+    render::Renderer,
+};
+
+use super::{
+    play::{Practice, RenderData},
+    State,
+};
+
+#[cfg(test)]
+mod tests;
+
+pub struct Editor {
+    actions: BTreeSet<Action>,
+    maps: Maps,
+    // the map is what gets edited and saved, the entities are only there to draw it
+    map: Map,
+    entities: Entities,
+    forge: Forge,
+    renderer: Renderer,
+    render_data: RenderData,
+    camera: Camera2D,
+    tool: Tool,
+    pickup: PickupKind,
+    well: WellKind,
+    selected: Option<Item>,
+    // where the item sits relative to the mouse, so it doesn't jump when we grab it
+    grab: Option<Vector2>,
+    mouse: Vector2,
+    status: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    Spawn,
+    Base,
+    Obstacle,
+    Pickup,
+    Well,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Item {
+    Spawn(usize),
+    Base(usize),
+    Obstacle(usize),
+    Pickup(usize),
+    Well(usize),
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Action {
+    Save,
+    Load,
+    New,
+    TestPlay,
+    Menu,
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        let maps = Maps::load();
+        let map = maps.maps[0].clone();
+
+        Self {
+            actions: BTreeSet::new(),
+            maps,
+            map,
+            entities: Entities::new(),
+            forge: Forge::new(),
+            renderer: Renderer::new(),
+            render_data: RenderData {
+                target: None,
+                target_timer: 0,
+                player_entity_id: None,
+            },
+            camera: Camera2D {
+                offset: Vector2 {
+                    x: (VIEWPORT_WIDTH / 2) as f32,
+                    y: (VIEWPORT_HEIGHT / 2) as f32,
+                },
+                target: Vector2::zero(),
+                rotation: 0.0,
+                zoom: 1.0,
+            },
+            tool: Tool::Spawn,
+            pickup: PickupKind::Health,
+            well: WellKind::Planet,
+            selected: None,
+            grab: None,
+            mouse: Vector2::zero(),
+            status: String::new(),
+        }
+    }
+
+    pub fn init(&mut self, _bus: &mut Bus) {
+        // pick up whatever has been saved since we were last here
+        self.maps = Maps::load();
+
+        let idx = self
+            .maps
+            .maps
+            .iter()
+            .position(|x| x.path.is_some() && x.path == self.map.path)
+            .unwrap_or(0);
+
+        self.open(self.maps.maps[idx].clone());
+    }
+
+    pub fn exit(&mut self) {
+        self.selected = None;
+        self.grab = None;
+        self.status.clear();
+    }
+
+    pub fn update(&mut self, _h: &mut RaylibHandle, bus: &mut Bus) {
+        self.action(bus);
+    }
+
+    pub fn input(&mut self, h: &mut RaylibHandle) {
+        if h.is_key_pressed(KeyboardKey::KEY_S) {
+            self.actions.insert(Action::Save);
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_L) {
+            self.actions.insert(Action::Load);
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_N) {
+            self.actions.insert(Action::New);
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_T) {
+            self.actions.insert(Action::TestPlay);
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_B) {
+            self.actions.insert(Action::Menu);
+        }
+
+        let tools = [
+            (KeyboardKey::KEY_ONE, Tool::Spawn),
+            (KeyboardKey::KEY_TWO, Tool::Base),
+            (KeyboardKey::KEY_THREE, Tool::Obstacle),
+            (KeyboardKey::KEY_FOUR, Tool::Pickup),
+            (KeyboardKey::KEY_FIVE, Tool::Well),
+        ];
+
+        for (key, tool) in tools {
+            if h.is_key_pressed(key) {
+                self.tool = tool;
+            }
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_K) {
+            match self.tool {
+                Tool::Pickup => self.pickup = next_pickup(self.pickup),
+                Tool::Well => self.well = next_well(self.well),
+                _ => (),
+            }
+        }
+
+        // editing happens as often as we draw, there's no simulation to wait for
+        // and dragging things around at the tick rate would feel sluggish
+        self.input_camera(h);
+        self.input_mouse(h);
+    }
+
+    pub fn draw(&mut self, r: &mut RaylibTextureMode<RaylibDrawHandle>, _delta: f32) {
+        {
+            let mut r = r.begin_mode2D(self.camera);
+
+            let zoom = self.camera.zoom;
+            let viewport = Rectangle {
+                x: self.camera.target.x - self.camera.offset.x / zoom,
+                y: self.camera.target.y - self.camera.offset.y / zoom,
+                width: self.camera.offset.x * 2.0 / zoom,
+                height: self.camera.offset.y * 2.0 / zoom,
+            };
+
+            // nothing moves in here, no need to interpolate
+            self.renderer.draw(
+                &mut r,
+                &self.entities,
+                &self.render_data,
+                &self.map,
+                viewport,
+                false,
+                1.0,
+            );
+
+            // spawns aren't entities, there's nothing to draw them by otherwise
+            let radius = EDITOR_PICK_RADIUS / zoom;
+            for (i, (x, y)) in self.map.spawns.iter().enumerate() {
+                r.draw_circle_lines(*x as i32, *y as i32, radius, DEBUG_COLOR);
+                r.draw_text(
+                    &i.to_string(),
+                    (*x + radius) as i32,
+                    (*y + radius) as i32,
+                    (10.0 / zoom) as i32,
+                    DEBUG_COLOR,
+                );
+            }
+
+            if let Some(item) = self.selected {
+                let p = self.position(item);
+                let radius = radius.max(self.size(item));
+                r.draw_circle_lines(p.x as i32, p.y as i32, radius, Color::RED);
+            }
+        }
+
+        self.draw_panel(r);
+    }
+
+    pub fn message(&mut self, _msg: &Message) {}
+
+    fn draw_panel(&self, r: &mut RaylibTextureMode<RaylibDrawHandle>) {
+        r.draw_rectangle(HUD_X, HUD_Y, HUD_WIDTH, HUD_HEIGHT, HUD_BACKGROUND_COLOR);
+        r.draw_line(HUD_X, HUD_Y, HUD_X, HUD_Y + HUD_HEIGHT, HUD_SEPARATOR_COLOR);
+
+        let tool = match self.tool {
+            Tool::Spawn => "spawn".to_owned(),
+            Tool::Base => "base".to_owned(),
+            Tool::Obstacle => "obstacle".to_owned(),
+            Tool::Pickup => format!("pickup {:?}", self.pickup),
+            Tool::Well => format!("well {:?}", self.well),
+        };
+
+        let lines = [
+            self.map.name.clone(),
+            format!("{} x {}", self.map.width, self.map.height),
+            String::new(),
+            tool,
+            String::new(),
+            "[1] spawn".to_owned(),
+            "[2] base".to_owned(),
+            "[3] obstacle".to_owned(),
+            "[4] pickup".to_owned(),
+            "[5] well".to_owned(),
+            "[k]ind".to_owned(),
+            String::new(),
+            "left place/drag".to_owned(),
+            "right remove".to_owned(),
+            "middle/arrows pan".to_owned(),
+            "wheel zoom".to_owned(),
+            String::new(),
+            "[s]ave".to_owned(),
+            "[l]oad".to_owned(),
+            "[n]ew".to_owned(),
+            "[t]est play".to_owned(),
+            "[b]ack".to_owned(),
+            String::new(),
+            self.status.clone(),
+        ];
+
+        for (i, line) in lines.iter().enumerate() {
+            r.draw_text(line, HUD_X + 8, HUD_Y + 10 + i as i32 * 13, 10, DEBUG_COLOR);
+        }
+    }
+
+    fn input_camera(&mut self, h: &mut RaylibHandle) {
+        let mouse = h.get_mouse_position();
+        let zoom = self.camera.zoom;
+
+        if h.is_mouse_button_down(MouseButton::MOUSE_BUTTON_MIDDLE) {
+            self.camera.target -= (mouse - self.mouse) / zoom;
+        }
+
+        self.mouse = mouse;
+
+        let step = EDITOR_PAN_SPEED * h.get_frame_time() / zoom;
+        let directions = [
+            (KeyboardKey::KEY_LEFT, Vector2::new(-step, 0.0)),
+            (KeyboardKey::KEY_RIGHT, Vector2::new(step, 0.0)),
+            (KeyboardKey::KEY_UP, Vector2::new(0.0, -step)),
+            (KeyboardKey::KEY_DOWN, Vector2::new(0.0, step)),
+        ];
+
+        for (key, direction) in directions {
+            if h.is_key_down(key) {
+                self.camera.target += direction;
+            }
+        }
+
+        let wheel = h.get_mouse_wheel_move();
+
+        if wheel != 0.0 {
+            // zoom in on the mouse, whatever is under it stays under it
+            let before = h.get_screen_to_world2D(mouse, self.camera);
+            self.camera.zoom = (zoom * (1.0 + wheel * 0.1)).clamp(EDITOR_ZOOM_MIN, EDITOR_ZOOM_MAX);
+            let after = h.get_screen_to_world2D(mouse, self.camera);
+
+            self.camera.target += before - after;
+        }
+    }
+
+    fn input_mouse(&mut self, h: &mut RaylibHandle) {
+        let mouse = h.get_mouse_position();
+        let world = h.get_screen_to_world2D(mouse, self.camera);
+
+        if h.is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.grab = None;
+        }
+
+        // the panel on the right isn't part of the cosmos
+        if mouse.x >= VIEWPORT_WIDTH as f32 {
+            return;
+        }
+
+        if h.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            // grab what's under the mouse, otherwise put something new there
+            let item = self.pick(world).unwrap_or_else(|| self.place(world));
+
+            self.selected = Some(item);
+            self.grab = Some(self.position(item) - world);
+            self.rebuild();
+        }
+
+        if let (Some(item), Some(grab)) = (self.selected, self.grab) {
+            if h.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                self.move_to(item, world + grab);
+                self.rebuild();
+            }
+        }
+
+        if h.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT) {
+            if let Some(item) = self.pick(world) {
+                self.remove(item);
+                self.selected = None;
+                self.grab = None;
+                self.rebuild();
+            }
+        }
+    }
+
+    fn action(&mut self, bus: &mut Bus) {
+        while let Some(action) = self.actions.pop_last() {
+            match action {
+                Action::Save => {
+                    self.status = match self.map.save() {
+                        Ok(()) => format!("saved {}", self.map.name),
+                        Err(error) => error,
+                    };
+                }
+                Action::Load => {
+                    self.maps = Maps::load();
+
+                    // go through the maps one by one
+                    let idx = self
+                        .maps
+                        .maps
+                        .iter()
+                        .position(|x| x.path.is_some() && x.path == self.map.path)
+                        .map_or(0, |x| (x + 1) % self.maps.maps.len());
+
+                    self.open(self.maps.maps[idx].clone());
+                    self.status = format!("loaded {}", self.map.name);
+                }
+                Action::New => {
+                    self.open(Map::untitled());
+                    self.status = "new map".to_owned();
+                }
+                Action::TestPlay => {
+                    // play goes by what's on disk, so it has to be saved first
+                    if let Err(error) = self.map.save() {
+                        self.status = error;
+                        continue;
+                    }
+
+                    self.maps = Maps::load();
+
+                    // the file that was just written, the name could be on some other file too
+                    let map = self
+                        .maps
+                        .maps
+                        .iter()
+                        .position(|x| x.path.is_some() && x.path == self.map.path);
+
+                    let Some(map) = map else {
+                        self.status = format!("{} went missing", self.map.name);
+                        continue;
+                    };
+
+                    bus.send(StateRequestMessage::Set(State::Practice(Practice {
+                        bots: PRACTICE_BOTS,
+                        map,
+                        editor: true,
+                    })));
+                }
+                Action::Menu => {
+                    bus.send(StateRequestMessage::Set(State::Menu));
+                }
+            }
+        }
+    }
+
+    fn open(&mut self, map: Map) {
+        self.map = map;
+        self.selected = None;
+        self.grab = None;
+
+        // start out looking at the whole thing
+        let width = self.map.width as f32;
+        let height = self.map.height as f32;
+
+        self.camera.target = Vector2::new(width / 2.0, height / 2.0);
+        self.camera.zoom = (VIEWPORT_WIDTH as f32 / width)
+            .min(VIEWPORT_HEIGHT as f32 / height)
+            .clamp(EDITOR_ZOOM_MIN, EDITOR_ZOOM_MAX);
+
+        self.rebuild();
+    }
+
+    fn rebuild(&mut self) {
+        let mut entities = Entities::new();
+
+        for vertexes in self.map.obstacles.iter() {
+            entities.add(Entity::Obstacle(self.forge.obstacle(vertexes)));
+        }
+
+        for (team, (x, y)) in self.map.bases.iter().enumerate() {
+            let base = self.forge.base(Vector2::new(*x, *y), team as u8);
+            entities.add(Entity::Base(base));
+        }
+
+        // randomly placed ones only exist once the seed is known
+        if let Placement::Fixed(wells) = &self.map.wells {
+            for (x, y, kind) in wells.iter() {
+                let well = self.forge.well(Vector2::new(*x, *y), *kind);
+                entities.add(Entity::Well(well));
+            }
+        }
+
+        if let Placement::Fixed(pickups) = &self.map.pickups {
+            for (x, y, kind) in pickups.iter() {
+                let pickup = self.forge.pickup(Vector2::new(*x, *y), *kind);
+                entities.add(Entity::Pickup(pickup));
+            }
+        }
+
+        self.entities = entities;
+    }
+
+    fn items(&self) -> Vec<Item> {
+        let map = &self.map;
+
+        // front to back, the small things are drawn over the big ones
+        let mut items = Vec::new();
+
+        if let Placement::Fixed(pickups) = &map.pickups {
+            items.extend((0..pickups.len()).map(Item::Pickup));
+        }
+
+        items.extend((0..map.spawns.len()).map(Item::Spawn));
+
+        if let Placement::Fixed(wells) = &map.wells {
+            items.extend((0..wells.len()).map(Item::Well));
+        }
+
+        items.extend((0..map.bases.len()).map(Item::Base));
+        items.extend((0..map.obstacles.len()).map(Item::Obstacle));
+
+        items
+    }
+
+    fn pick(&self, world: Vector2) -> Option<Item> {
+        let radius = EDITOR_PICK_RADIUS / self.camera.zoom;
+
+        self.items().into_iter().find(|item| match item {
+            Item::Obstacle(idx) => inside(&self.map.obstacles[*idx], world),
+            _ => (self.position(*item) - world).length() <= radius.max(self.size(*item)),
+        })
+    }
+
+    fn place(&mut self, world: Vector2) -> Item {
+        let map = &mut self.map;
+        let (x, y) = (world.x, world.y);
+
+        match self.tool {
+            Tool::Spawn => {
+                map.spawns.push((x, y));
+                Item::Spawn(map.spawns.len() - 1)
+            }
+            Tool::Base => {
+                map.bases.push((x, y));
+                Item::Base(map.bases.len() - 1)
+            }
+            Tool::Obstacle => {
+                // a square is always convex, which is all the collisions can handle
+                let half = EDITOR_OBSTACLE_SIZE / 2.0;
+                map.obstacles.push(vec![
+                    (x - half, y - half),
+                    (x + half, y - half),
+                    (x + half, y + half),
+                    (x - half, y + half),
+                ]);
+                Item::Obstacle(map.obstacles.len() - 1)
+            }
+            Tool::Pickup => {
+                // placing one by hand means placing all of them by hand
+                if let Placement::Random(_) = map.pickups {
+                    map.pickups = Placement::Fixed(Vec::new());
+                }
+
+                let Placement::Fixed(pickups) = &mut map.pickups else {
+                    panic!("wtf editor pickups");
+                };
+
+                pickups.push((x, y, self.pickup));
+                Item::Pickup(pickups.len() - 1)
+            }
+            Tool::Well => {
+                if let Placement::Random(_) = map.wells {
+                    map.wells = Placement::Fixed(Vec::new());
+                }
+
+                let Placement::Fixed(wells) = &mut map.wells else {
+                    panic!("wtf editor wells");
+                };
+
+                wells.push((x, y, self.well));
+                Item::Well(wells.len() - 1)
+            }
+        }
+    }
+
+    fn remove(&mut self, item: Item) {
+        let map = &mut self.map;
+
+        match item {
+            // a map can't do without somewhere to spawn
+            Item::Spawn(_) if map.spawns.len() == 1 => {
+                self.status = "need a spawn".to_owned();
+            }
+            Item::Base(_) if map.bases.len() == 1 => {
+                self.status = "need a base".to_owned();
+            }
+            Item::Spawn(idx) => {
+                map.spawns.remove(idx);
+            }
+            Item::Base(idx) => {
+                map.bases.remove(idx);
+            }
+            Item::Obstacle(idx) => {
+                map.obstacles.remove(idx);
+            }
+            Item::Pickup(idx) => {
+                if let Placement::Fixed(pickups) = &mut map.pickups {
+                    pickups.remove(idx);
+                }
+            }
+            Item::Well(idx) => {
+                if let Placement::Fixed(wells) = &mut map.wells {
+                    wells.remove(idx);
+                }
+            }
+        }
+    }
+
+    fn position(&self, item: Item) -> Vector2 {
+        let map = &self.map;
+
+        let (x, y) = match (item, &map.pickups, &map.wells) {
+            (Item::Spawn(idx), _, _) => map.spawns[idx],
+            (Item::Base(idx), _, _) => map.bases[idx],
+            (Item::Obstacle(idx), _, _) => {
+                // obstacles are moved around by their center
+                let vertexes = &map.obstacles[idx];
+                let (x, y) = vertexes
+                    .iter()
+                    .fold((0.0, 0.0), |(x, y), v| (x + v.0, y + v.1));
+                let len = vertexes.len() as f32;
+                (x / len, y / len)
+            }
+            (Item::Pickup(idx), Placement::Fixed(pickups), _) => {
+                let (x, y, _) = pickups[idx];
+                (x, y)
+            }
+            (Item::Well(idx), _, Placement::Fixed(wells)) => {
+                let (x, y, _) = wells[idx];
+                (x, y)
+            }
+            _ => panic!("wtf editor item"),
+        };
+
+        Vector2::new(x, y)
+    }
+
+    fn move_to(&mut self, item: Item, position: Vector2) {
+        let delta = position - self.position(item);
+        let map = &mut self.map;
+
+        let moved = |(x, y): &mut (f32, f32)| {
+            *x += delta.x;
+            *y += delta.y;
+        };
+
+        match (item, &mut map.pickups, &mut map.wells) {
+            (Item::Spawn(idx), _, _) => moved(&mut map.spawns[idx]),
+            (Item::Base(idx), _, _) => moved(&mut map.bases[idx]),
+            (Item::Obstacle(idx), _, _) => map.obstacles[idx].iter_mut().for_each(moved),
+            (Item::Pickup(idx), Placement::Fixed(pickups), _) => {
+                let (x, y, _) = &mut pickups[idx];
+                (*x, *y) = (*x + delta.x, *y + delta.y);
+            }
+            (Item::Well(idx), _, Placement::Fixed(wells)) => {
+                let (x, y, _) = &mut wells[idx];
+                (*x, *y) = (*x + delta.x, *y + delta.y);
+            }
+            _ => panic!("wtf editor item"),
+        }
+    }
+
+    // how far out from its position an item can still be grabbed
+    fn size(&self, item: Item) -> f32 {
+        match (item, &self.map.wells) {
+            // same as the forge makes them
+            (Item::Base(_), _) => 150.0,
+            (Item::Well(idx), Placement::Fixed(wells)) => match wells[idx].2 {
+                WellKind::Planet => WELL_PLANET_RADIUS,
+                WellKind::BlackHole => WELL_BLACK_HOLE_RADIUS,
+            },
+            _ => 0.0,
+        }
+    }
+}
+
+fn next_pickup(kind: PickupKind) -> PickupKind {
+    match kind {
+        PickupKind::Health => PickupKind::Boost,
+        PickupKind::Boost => PickupKind::Ammo,
+        PickupKind::Ammo => PickupKind::RapidFire,
+        PickupKind::RapidFire => PickupKind::Health,
+    }
+}
+
+fn next_well(kind: WellKind) -> WellKind {
+    match kind {
+        WellKind::Planet => WellKind::BlackHole,
+        WellKind::BlackHole => WellKind::Planet,
+    }
+}
+
+// convex only, the point has to be on the same side of every edge
+fn inside(vertexes: &[(f32, f32)], p: Vector2) -> bool {
+    let mut sign = 0.0;
+
+    for i in 0..vertexes.len() {
+        let (x1, y1) = vertexes[i];
+        let (x2, y2) = vertexes[(i + 1) % vertexes.len()];
+
+        let cross = (x2 - x1) * (p.y - y1) - (y2 - y1) * (p.x - x1);
+
+        if cross == 0.0 {
+            continue;
+        }
+
+        if sign == 0.0 {
+            sign = cross.signum();
+        } else if cross.signum() != sign {
+            return false;
+        }
+    }
+
+    sign != 0.0
+}
@@ -0,0 +1,49 @@
+use super::*;
+
+// place one of everything, drag it somewhere else and take it away again
+#[test]
+fn place_drag_remove() {
+    let mut editor = Editor::new();
+    editor.open(Map::blank("test"));
+
+    let tools = [
+        Tool::Spawn,
+        Tool::Base,
+        Tool::Obstacle,
+        Tool::Pickup,
+        Tool::Well,
+    ];
+
+    for tool in tools {
+        editor.tool = tool;
+
+        let at = Vector2::new(2000.0, 4000.0);
+        let to = Vector2::new(2500.0, 4500.0);
+        let before = editor.items().len();
+
+        let item = editor.place(at);
+        assert!(editor.pick(at) == Some(item));
+        assert_eq!(editor.items().len(), before + 1);
+
+        editor.move_to(item, to);
+        assert!(editor.pick(at).is_none());
+        assert!(editor.pick(to) == Some(item));
+        assert!((editor.position(item) - to).length() < 0.001);
+
+        editor.remove(item);
+        assert!(editor.pick(to).is_none());
+        assert_eq!(editor.items().len(), before);
+    }
+
+    // the last spawn and base have to stay
+    editor.remove(Item::Spawn(0));
+    editor.remove(Item::Base(0));
+    editor.remove(Item::Base(0));
+    assert_eq!(editor.map.spawns.len(), 1);
+    assert_eq!(editor.map.bases.len(), 1);
+
+    // and whatever we end up with loads again
+    editor.rebuild();
+    let back: Map = ron::from_str(&editor.map.to_ron()).expect("wtf editor map");
+    assert_eq!(back.obstacles, editor.map.obstacles);
+}
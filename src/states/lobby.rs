@@ -4,8 +4,11 @@ use raylib::prelude::*;
 
 use crate::{
     bus::Bus,
-    constants::{DEBUG_COLOR, RENDER_WIDTH},
+    constants::{BOTS_MAX, DEBUG_COLOR, RENDER_WIDTH},
+    maps::Maps,
     messages::{Message, NetMessage, NetRequestMessage, StateRequestMessage},
+    settings::Settings,
+    ships::Ships,
 };
 
 use super::State;
@@ -14,15 +17,32 @@ pub struct Lobby {
     actions: BTreeSet<Action>,
     start_text: String,
     leave_text: String,
+    mode_text: String,
+    boundary_text: String,
+    physics_text: String,
+    map_text: String,
+    class_text: String,
+    bots_text: String,
+    difficulty_text: String,
+    ships: Ships,
+    maps: Maps,
     client_id: u32,
     client_ids: Vec<u32>,
     seed: u32,
+    settings: Settings,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 enum Action {
     Leave,
     Start,
+    Mode,
+    Boundary,
+    Physics,
+    Map,
+    Class,
+    Bots,
+    Difficulty,
     Play,
     Menu,
 }
@@ -33,13 +53,26 @@ impl Lobby {
             actions: BTreeSet::new(),
             start_text: "[s]tart".to_owned(),
             leave_text: "[l]eave".to_owned(),
+            mode_text: "[m]ode".to_owned(),
+            boundary_text: "[b]oundary".to_owned(),
+            physics_text: "[p]hysics".to_owned(),
+            map_text: "m[a]p".to_owned(),
+            class_text: "[c]lass".to_owned(),
+            bots_text: "b[o]ts".to_owned(),
+            difficulty_text: "[d]ifficulty".to_owned(),
+            ships: Ships::load(),
+            maps: Maps::load(),
             client_id: 0,
             client_ids: Vec::new(),
             seed: 0,
+            settings: Settings::new(),
         }
     }
 
     pub fn init(&mut self, bus: &mut Bus) {
+        // maps could have been saved in the editor since
+        self.maps = Maps::load();
+
         bus.send(NetRequestMessage::Synchronize);
     }
 
@@ -47,6 +80,7 @@ impl Lobby {
         self.client_id = 0;
         self.client_ids.clear();
         self.seed = 0;
+        self.settings = Settings::new();
     }
 
     pub fn update(&mut self, _h: &mut RaylibHandle, bus: &mut Bus) {
@@ -61,6 +95,34 @@ impl Lobby {
         if h.is_key_pressed(KeyboardKey::KEY_S) {
             self.actions.insert(Action::Start);
         }
+
+        if h.is_key_pressed(KeyboardKey::KEY_M) {
+            self.actions.insert(Action::Mode);
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_B) {
+            self.actions.insert(Action::Boundary);
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_P) {
+            self.actions.insert(Action::Physics);
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_A) {
+            self.actions.insert(Action::Map);
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_C) {
+            self.actions.insert(Action::Class);
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_O) {
+            self.actions.insert(Action::Bots);
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_D) {
+            self.actions.insert(Action::Difficulty);
+        }
     }
 
     pub fn draw(&mut self, r: &mut RaylibTextureMode<RaylibDrawHandle>, _delta: f32) {
@@ -102,14 +164,119 @@ impl Lobby {
             20,
             DEBUG_COLOR,
         );
+
+        r.draw_text(&self.mode_text, RENDER_WIDTH / 2 - 50, 200, 20, DEBUG_COLOR);
+
+        r.draw_text(
+            self.settings.mode.name(),
+            RENDER_WIDTH / 2 - 50,
+            220,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            &self.boundary_text,
+            RENDER_WIDTH / 2 - 50,
+            260,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            self.settings.boundary.name(),
+            RENDER_WIDTH / 2 - 50,
+            280,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            &self.physics_text,
+            RENDER_WIDTH / 2 - 50,
+            320,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            self.settings.physics.name(),
+            RENDER_WIDTH / 2 - 50,
+            340,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(&self.map_text, RENDER_WIDTH / 2 - 50, 380, 20, DEBUG_COLOR);
+
+        r.draw_text(
+            &self.settings.map,
+            RENDER_WIDTH / 2 - 50,
+            400,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            &self.bots_text,
+            RENDER_WIDTH / 2 + 150,
+            200,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            &self.settings.bots.to_string(),
+            RENDER_WIDTH / 2 + 150,
+            220,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            &self.difficulty_text,
+            RENDER_WIDTH / 2 + 150,
+            260,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            self.settings.difficulty.name(),
+            RENDER_WIDTH / 2 + 150,
+            280,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            &self.class_text,
+            RENDER_WIDTH / 2 - 50,
+            440,
+            20,
+            DEBUG_COLOR,
+        );
+
+        for (i, cid) in self.client_ids.iter().enumerate() {
+            let class = self.settings.class(*cid);
+
+            r.draw_text(
+                &format!("{} {}", cid, self.ships.class(class).name),
+                RENDER_WIDTH / 2 - 50,
+                460 + i as i32 * 20,
+                20,
+                DEBUG_COLOR,
+            );
+        }
     }
 
     pub fn message(&mut self, msg: &Message) {
         match msg {
             Message::Net(msg) => match msg {
-                NetMessage::Synchronize(seed, cid, cids) => {
+                NetMessage::Synchronize(seed, cid, settings, cids) => {
                     self.seed = *seed;
                     self.client_id = *cid;
+                    self.settings = settings.clone();
                     self.client_ids = cids.to_vec();
                 }
                 NetMessage::Disconnected => {
@@ -133,6 +300,60 @@ impl Lobby {
                 Action::Start => {
                     bus.send(NetRequestMessage::Start);
                 }
+                Action::Mode => {
+                    // only the host gets to change the settings, net ignores the rest
+                    bus.send(NetRequestMessage::Settings(Settings {
+                        mode: self.settings.mode.next(),
+                        ..self.settings.clone()
+                    }));
+                }
+                Action::Boundary => {
+                    bus.send(NetRequestMessage::Settings(Settings {
+                        boundary: self.settings.boundary.next(),
+                        ..self.settings.clone()
+                    }));
+                }
+                Action::Physics => {
+                    bus.send(NetRequestMessage::Settings(Settings {
+                        physics: self.settings.physics.next(),
+                        ..self.settings.clone()
+                    }));
+                }
+                Action::Map => {
+                    let idx = self
+                        .maps
+                        .maps
+                        .iter()
+                        .position(|x| {
+                            x.name == self.settings.map && x.hash == self.settings.map_hash
+                        })
+                        .map_or(0, |x| (x + 1) % self.maps.maps.len());
+                    let map = &self.maps.maps[idx];
+
+                    bus.send(NetRequestMessage::Settings(Settings {
+                        map: map.name.clone(),
+                        map_hash: map.hash,
+                        ..self.settings.clone()
+                    }));
+                }
+                Action::Class => {
+                    let class = self.settings.class(self.client_id) as usize + 1;
+                    let class = (class % self.ships.classes.len()) as u8;
+
+                    bus.send(NetRequestMessage::Class(class));
+                }
+                Action::Bots => {
+                    bus.send(NetRequestMessage::Settings(Settings {
+                        bots: (self.settings.bots + 1) % (BOTS_MAX + 1),
+                        ..self.settings.clone()
+                    }));
+                }
+                Action::Difficulty => {
+                    bus.send(NetRequestMessage::Settings(Settings {
+                        difficulty: self.settings.difficulty.next(),
+                        ..self.settings.clone()
+                    }));
+                }
                 Action::Play => {
                     bus.send(StateRequestMessage::Set(State::Play));
                 }
@@ -4,11 +4,11 @@ use raylib::prelude::*;
 
 use crate::{
     bus::Bus,
-    constants::{DEBUG_COLOR, RENDER_WIDTH},
+    constants::{DEBUG_COLOR, PRACTICE_BOTS, RENDER_WIDTH},
     messages::{Message, NetMessage, NetRequestMessage, StateRequestMessage},
 };
 
-use super::State;
+use super::{play::Practice, State};
 
 pub struct Menu {
     actions: BTreeSet<Action>,
@@ -17,12 +17,17 @@ pub struct Menu {
     joining: bool,
     host_text: String,
     join_text: String,
+    practice_text: String,
+    free_flight_text: String,
+    editor_text: String,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 enum Action {
     Host,
     Join(String),
+    Practice(u8),
+    Editor,
     Hosted,
     Connected,
     Disconnected,
@@ -37,6 +42,9 @@ impl Menu {
             joining: false,
             host_text: "[h]ost".to_owned(),
             join_text: "[j]oin".to_owned(),
+            practice_text: "[p]ractice".to_owned(),
+            free_flight_text: "[f]ree flight".to_owned(),
+            editor_text: "[e]ditor".to_owned(),
         }
     }
 
@@ -60,12 +68,51 @@ impl Menu {
         if h.is_key_pressed(KeyboardKey::KEY_J) && !self.joining {
             self.actions.insert(Action::Join("127.0.0.1".to_owned()));
         }
+
+        // neither needs a server, so don't start one while we're hosting or joining
+        let busy = self.hosting || self.joining;
+
+        if h.is_key_pressed(KeyboardKey::KEY_P) && !busy {
+            self.actions.insert(Action::Practice(PRACTICE_BOTS));
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_F) && !busy {
+            self.actions.insert(Action::Practice(0));
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_E) && !busy {
+            self.actions.insert(Action::Editor);
+        }
     }
 
     pub fn draw(&mut self, r: &mut RaylibTextureMode<RaylibDrawHandle>, _delta: f32) {
         r.draw_text(&self.host_text, RENDER_WIDTH / 2 - 50, 100, 20, DEBUG_COLOR);
 
         r.draw_text(&self.join_text, RENDER_WIDTH / 2 - 50, 120, 20, DEBUG_COLOR);
+
+        r.draw_text(
+            &self.practice_text,
+            RENDER_WIDTH / 2 - 50,
+            160,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            &self.free_flight_text,
+            RENDER_WIDTH / 2 - 50,
+            180,
+            20,
+            DEBUG_COLOR,
+        );
+
+        r.draw_text(
+            &self.editor_text,
+            RENDER_WIDTH / 2 - 50,
+            220,
+            20,
+            DEBUG_COLOR,
+        );
     }
 
     pub fn message(&mut self, msg: &Message) {
@@ -107,6 +154,16 @@ impl Menu {
 
                     bus.send(NetRequestMessage::Connect(host));
                 }
+                Action::Practice(bots) => {
+                    bus.send(StateRequestMessage::Set(State::Practice(Practice {
+                        bots,
+                        map: 0,
+                        editor: false,
+                    })));
+                }
+                Action::Editor => {
+                    bus.send(StateRequestMessage::Set(State::Editor));
+                }
                 Action::Hosted => {
                     self.hosted = true;
                     self.actions.insert(Action::Join("127.0.0.1".to_owned()));
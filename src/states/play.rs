@@ -1,29 +1,35 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet};
 
 use raylib::prelude::*;
 
 use crate::{
     bus::Bus,
     commands::{Command, EntityCommands, Spawn},
-    components::{Centroidable, Generation},
+    components::{Centroidable, EffectKind, Generation, Lerpable, Slot},
     constants::{
-        COSMOS_HEIGHT, COSMOS_WIDTH, DEBUG_COLOR, HUD_BACKGROUND_COLOR, HUD_HEIGHT,
-        HUD_SEPARATOR_COLOR, HUD_WIDTH, HUD_X, HUD_Y, MINIMAP_AREA_HEIGHT, MINIMAP_AREA_WIDTH,
-        MINIMAP_HEIGHT, MINIMAP_WIDTH, MINIMAP_X, MINIMAP_Y, RENDER_WIDTH, RESPAWN_TIMER,
+        BOT_CLIENT_ID, DEBUG_COLOR, HUD_BACKGROUND_COLOR, HUD_HEIGHT, HUD_SEPARATOR_COLOR,
+        HUD_WIDTH, HUD_X, HUD_Y, MINIMAP_AREA_HEIGHT, MINIMAP_AREA_WIDTH, MINIMAP_HEIGHT,
+        MINIMAP_WIDTH, MINIMAP_X, MINIMAP_Y, RENDER_WIDTH, RESPAWN_TIMER, TEAM_COLORS,
         TICK_SCHEDULED, VIEWPORT_HEIGHT, VIEWPORT_WIDTH,
     },
-    entities::{Entities, Entity, EntityIndex},
+    entities::{Eid, Entities, Entity, EntityIndex},
     forge::Forge,
-    logic::Logic,
+    logic::{bots::Bot, objectives::flag_home, Logic},
+    maps::{Map, Maps},
     messages::{
         EngineMessage, EngineRequestMessage, LogicMessage, Message, NetMessage, NetRequestMessage,
+        StateRequestMessage,
     },
     quadtree::QuadTree,
-    render::Renderer,
+    render::{draw_arrow, Renderer},
+    settings::{Mode, Settings},
     utils::minimap_translate,
 };
 
+use super::State;
+
 pub struct Play {
+    source: Source,
     tick: u32,
     synchronized: bool,
     debug: bool,
@@ -42,6 +48,25 @@ pub struct Play {
     actions: BTreeSet<Action>,
     render_data: RenderData,
     quadtree: QuadTree,
+    maps: Maps,
+    map: usize,
+}
+
+// where the commands of every tick come from, the network or straight back from ourselves
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Network,
+    // playing alone, nothing goes over the wire
+    Loopback(Practice),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Practice {
+    pub bots: u8,
+    // index into the maps as they are on disk right now, the editor might have just saved one
+    pub map: usize,
+    // test play from the editor, leaving goes back there
+    pub editor: bool,
 }
 
 struct TickCommands {
@@ -53,48 +78,79 @@ struct NetworkData {
     client_id: u32,
     client_ids: Vec<u32>,
     seed: u32,
+    settings: Settings,
 }
 
 pub struct RenderData {
-    pub target: Option<usize>,
+    pub target: Option<Eid>,
     pub target_timer: u8,
-    pub target_eidx: Option<EntityIndex>,
-    pub player_entity_id: usize,
-    pub player_eidx: Option<EntityIndex>,
+    pub player_entity_id: Option<Eid>,
 }
 
 struct PlayerData {
-    player_entity_id: usize,
+    player_entity_id: Option<Eid>,
     hud_data: HudData,
-    entity_ids: Vec<usize>,
-    map: HashMap<u32, usize>,
-    respawn_timers: Vec<(usize, u8)>,
+    entity_ids: Vec<Eid>,
+    map: BTreeMap<u32, Eid>,
+    teams: BTreeMap<u32, u8>,
+    respawn_timers: Vec<(Eid, u8)>,
+    // bots are players too, they just don't have a peer behind them
+    bots: BTreeMap<u32, Bot>,
 }
 
 struct HudData {
     life: f32,
+    shield: f32,
     speed: f32,
     boost_active: u8,
     boost_cooldown: u8,
-    torpedo_cooldown: u8,
-    target: Option<usize>,
+    dampeners: bool,
+    primary: (&'static str, u8, Option<u8>, u8),
+    secondary: (&'static str, u8, Option<u8>, u8),
+    flares: u8,
+    flares_cooldown: u8,
+    effects: Vec<(&'static str, u8)>,
+    target: Option<Eid>,
     target_timer: u8,
     minimap_entities: Vec<(Vector2, f32, Color)>,
+    minimap_found: Vec<EntityIndex>,
     minimap_xy: Vector2,
+    objectives: Vec<(u8, u32, FlagState)>,
+    lock: LockState,
+    incoming: usize,
+    threats: Vec<(Generation<Rectangle>, Color)>,
+}
+
+enum LockState {
+    None,
+    Locking,
+    Locked,
+}
+
+enum FlagState {
+    Home,
+    Taken,
+    Dropped,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 enum Action {
-    Synchronize(u32, u32, Vec<u32>),
+    Loopback(Practice),
+    Synchronize(u32, u32, Settings, Vec<u32>),
     Command(Command),
     ToggleInterpolation,
     ToggleDebug,
     TogglePause,
+    Leave,
 }
 
 impl Play {
     pub fn new() -> Self {
+        let maps = Maps::load();
+        let quadtree = QuadTree::new(maps.maps[0].width, maps.maps[0].height);
+
         Self {
+            source: Source::Network,
             tick: 0,
             synchronized: false,
             debug: false,
@@ -104,30 +160,42 @@ impl Play {
                 client_id: 0,
                 client_ids: Vec::new(),
                 seed: 0,
+                settings: Settings::new(),
             },
             player_data: PlayerData {
-                player_entity_id: 0,
+                player_entity_id: None,
                 hud_data: HudData {
                     life: 0.0,
+                    shield: 0.0,
                     speed: 0.0,
                     boost_active: 0,
                     boost_cooldown: 0,
-                    torpedo_cooldown: 0,
+                    dampeners: false,
+                    primary: ("-", 0, None, 0),
+                    secondary: ("-", 0, None, 0),
+                    flares: 0,
+                    flares_cooldown: 0,
+                    effects: Vec::new(),
                     target: None,
                     target_timer: 0,
                     minimap_entities: Vec::new(),
+                    minimap_found: Vec::new(),
                     minimap_xy: Vector2::zero(),
+                    objectives: Vec::new(),
+                    lock: LockState::None,
+                    incoming: 0,
+                    threats: Vec::new(),
                 },
                 entity_ids: Vec::new(),
-                map: HashMap::new(),
+                map: BTreeMap::new(),
+                teams: BTreeMap::new(),
                 respawn_timers: Vec::new(),
+                bots: BTreeMap::new(),
             },
             render_data: RenderData {
                 target: None,
                 target_timer: 0,
-                target_eidx: None,
-                player_entity_id: 0,
-                player_eidx: None,
+                player_entity_id: None,
             },
             camera: Camera2D {
                 offset: Vector2 {
@@ -149,23 +217,51 @@ impl Play {
             commands: Vec::new(),
             command_queue: BTreeSet::new(),
             actions: BTreeSet::new(),
-            quadtree: QuadTree::new(COSMOS_WIDTH, COSMOS_HEIGHT),
+            quadtree,
+            maps,
+            map: 0,
         }
     }
 
-    pub fn init(&mut self, bus: &mut Bus) {
+    pub fn init(&mut self, bus: &mut Bus, source: Source) {
+        self.source = source;
+
+        // maps could have been saved in the editor since
+        self.maps = Maps::load();
+
         // we must synchronize to get current options
         bus.send(EngineRequestMessage::Synchronize);
 
-        // we must synchronize to get all clients, local client, and rng seed
-        bus.send(NetRequestMessage::Synchronize);
+        match source {
+            // we must synchronize to get all clients, local client, and rng seed
+            Source::Network => bus.send(NetRequestMessage::Synchronize),
+            // or make all of that up ourselves
+            Source::Loopback(practice) => {
+                self.actions.insert(Action::Loopback(practice));
+            }
+        }
     }
 
     pub fn exit(&mut self) {
         self.tick = 0;
         self.synchronized = false;
+        self.paused = false;
         self.commands.clear();
         self.command_queue.clear();
+
+        // nothing of the last game may leak into the next one
+        let map = &self.maps.maps[self.map];
+        self.entities = Entities::new();
+        self.logic = Logic::new();
+        self.quadtree = QuadTree::new(map.width, map.height);
+
+        let p = &mut self.player_data;
+        p.player_entity_id = None;
+        p.entity_ids.clear();
+        p.map.clear();
+        p.teams.clear();
+        p.respawn_timers.clear();
+        p.bots.clear();
     }
 
     pub fn update(&mut self, h: &mut RaylibHandle, bus: &mut Bus) {
@@ -175,7 +271,7 @@ impl Play {
             return;
         }
 
-        let tick_commands = &self.commands[self.tick as usize];
+        let tick_commands = &mut self.commands[self.tick as usize];
 
         self.stalling = !tick_commands.ready;
 
@@ -183,12 +279,25 @@ impl Play {
             return;
         }
 
+        // every peer has the same state at this point, so the bots come up with
+        // the same commands everywhere
+        for (cid, bot) in self.player_data.bots.iter_mut() {
+            let id = self.player_data.map[cid];
+
+            tick_commands.commands.push(EntityCommands {
+                id,
+                commands: bot.commands(id, &self.entities),
+            });
+        }
+
         self.logic.update(
             bus,
             &mut self.entities,
             &tick_commands.commands,
             &self.forge,
             &mut self.quadtree,
+            &self.network_data.settings,
+            &self.maps.maps[self.map],
             h,
         );
 
@@ -201,11 +310,18 @@ impl Play {
             q.push(c);
         }
 
-        // send the current command queue
-        bus.send(NetRequestMessage::Commands(
-            self.tick + TICK_SCHEDULED,
-            q.into_boxed_slice(),
-        ));
+        // send the current command queue, or take it in right away if we're on our own
+        match self.source {
+            Source::Network => bus.send(NetRequestMessage::Commands(
+                self.tick + TICK_SCHEDULED,
+                q.into_boxed_slice(),
+            )),
+            Source::Loopback(_) => self.receive_commands(
+                self.network_data.client_id,
+                self.tick + TICK_SCHEDULED,
+                q.into_boxed_slice(),
+            ),
+        }
 
         // make sure we can receive the new commands
         self.commands.push(TickCommands {
@@ -230,6 +346,11 @@ impl Play {
             self.actions.insert(Action::TogglePause);
         }
 
+        // only when playing alone, leaving a network game goes through the lobby
+        if h.is_key_pressed(KeyboardKey::KEY_L) && matches!(self.source, Source::Loopback(_)) {
+            self.actions.insert(Action::Leave);
+        }
+
         if h.is_key_pressed(KeyboardKey::KEY_F4) {
             let pos = h.get_screen_to_world2D(h.get_mouse_position(), self.camera);
 
@@ -259,7 +380,7 @@ impl Play {
         }
 
         if h.is_key_down(KeyboardKey::KEY_SPACE) {
-            self.actions.insert(Action::Command(Command::Projectile));
+            self.actions.insert(Action::Command(Command::FirePrimary));
         }
 
         if h.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) {
@@ -267,12 +388,49 @@ impl Play {
         }
 
         if h.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) {
-            self.actions.insert(Action::Command(Command::Torpedo));
+            self.actions.insert(Action::Command(Command::FireSecondary));
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_Q) {
+            self.actions.insert(Action::Command(Command::CyclePrimary));
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_E) {
+            self.actions
+                .insert(Action::Command(Command::CycleSecondary));
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_X) {
+            self.actions
+                .insert(Action::Command(Command::Countermeasure));
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_Z) {
+            self.actions.insert(Action::Command(Command::Dampeners));
         }
 
         if h.is_key_pressed(KeyboardKey::KEY_TAB) {
             self.actions.insert(Action::Command(Command::TargetLock));
         }
+
+        if h.is_key_pressed(KeyboardKey::KEY_R) {
+            self.actions
+                .insert(Action::Command(Command::TargetPrevious));
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_T) {
+            self.actions
+                .insert(Action::Command(Command::TargetCrosshair));
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_G) {
+            self.actions.insert(Action::Command(Command::TargetWeakest));
+        }
+
+        if h.is_key_pressed(KeyboardKey::KEY_F) {
+            self.actions
+                .insert(Action::Command(Command::TargetAttacker));
+        }
     }
 
     pub fn draw(&mut self, r: &mut RaylibTextureMode<RaylibDrawHandle>, delta: f32) {
@@ -309,12 +467,20 @@ impl Play {
                 &mut r,
                 &self.entities,
                 &self.render_data,
+                &self.maps.maps[self.map],
                 viewport,
                 self.debug,
                 delta,
             );
+
+            // point towards every threat that is outside of the viewport
+            for (bounds, color) in self.player_data.hud_data.threats.iter() {
+                draw_arrow(&mut r, bounds.lerp(delta), viewport, *color);
+            }
         }
 
+        self.draw_warnings(r, delta);
+
         if self.stalling {
             let len = r.measure_text("stalling", 10);
             r.draw_text(
@@ -335,6 +501,12 @@ impl Play {
                 10,
                 DEBUG_COLOR,
             );
+
+            // there's a way out when playing alone
+            if let Source::Loopback(_) = self.source {
+                let len = r.measure_text("[l]eave", 10);
+                r.draw_text("[l]eave", RENDER_WIDTH / 2 - len / 2, 114, 10, DEBUG_COLOR);
+            }
         }
 
         self.draw_hud(r, delta);
@@ -364,24 +536,16 @@ impl Play {
     pub fn message(&mut self, msg: &Message) {
         match msg {
             Message::Net(msg) => match msg {
-                NetMessage::Synchronize(seed, cid, cids) => {
-                    self.actions
-                        .insert(Action::Synchronize(*seed, *cid, cids.to_vec()));
+                NetMessage::Synchronize(seed, cid, settings, cids) => {
+                    self.actions.insert(Action::Synchronize(
+                        *seed,
+                        *cid,
+                        settings.clone(),
+                        cids.to_vec(),
+                    ));
                 }
                 NetMessage::Commands(cid, tick, cmds) => {
-                    // TODO: this might panic, investigate, make sure the index exists before we access it?
-                    let tick_commands = &mut self.commands[*tick as usize];
-
-                    // add the client's commands
-                    tick_commands.commands.push(EntityCommands {
-                        id: self.player_data.map[cid],
-                        commands: cmds.clone(),
-                    });
-
-                    // if there are as many entity commands as there are players,
-                    // then we have received everything and are ready to progress
-                    tick_commands.ready =
-                        tick_commands.commands.len() == self.player_data.entity_ids.len();
+                    self.receive_commands(*cid, *tick, cmds.clone());
                 }
                 NetMessage::TogglePause(_cid) => {
                     // TODO: might be interesting to display who toggled pause
@@ -396,7 +560,7 @@ impl Play {
                         self.player_data.respawn_timers.push((*eid, RESPAWN_TIMER));
 
                         // if we have died, let's reset the player data
-                        if self.player_data.player_entity_id == *eid {
+                        if self.player_data.player_entity_id == Some(*eid) {
                             self.reset_data();
                         }
                     }
@@ -412,6 +576,38 @@ impl Play {
         }
     }
 
+    fn draw_warnings(&mut self, r: &mut RaylibTextureMode<RaylibDrawHandle>, _delta: f32) {
+        let data = &self.player_data.hud_data;
+
+        let mut warnings = Vec::new();
+
+        match data.lock {
+            LockState::None => (),
+            LockState::Locking => warnings.push(("LOCK WARNING", Color::ORANGE)),
+            LockState::Locked => warnings.push(("LOCKED ON", Color::RED)),
+        }
+
+        if data.incoming != 0 {
+            warnings.push(("MISSILE INCOMING", Color::RED));
+        }
+
+        // blink so it's hard to miss
+        if self.tick / 4 % 2 != 0 {
+            return;
+        }
+
+        for (i, (text, color)) in warnings.iter().enumerate() {
+            let len = r.measure_text(text, 20);
+            r.draw_text(
+                text,
+                VIEWPORT_WIDTH as i32 / 2 - len / 2,
+                40 + i as i32 * 24,
+                20,
+                *color,
+            );
+        }
+    }
+
     fn draw_hud(&mut self, r: &mut RaylibTextureMode<RaylibDrawHandle>, _delta: f32) {
         r.draw_rectangle(HUD_X, HUD_Y, HUD_WIDTH, HUD_HEIGHT, HUD_BACKGROUND_COLOR);
         r.draw_line(HUD_X, HUD_Y, HUD_X, HUD_Y + HUD_HEIGHT, HUD_SEPARATOR_COLOR);
@@ -436,14 +632,14 @@ impl Play {
         );
 
         r.draw_text(
-            "SPEED",
+            "SHIELD",
             HUD_X + pad_x,
             HUD_Y + 20 + pad_y * 2,
             10,
             DEBUG_COLOR,
         );
         r.draw_text(
-            &format!("{:.2}", data.speed),
+            &format!("{:.2}", data.shield),
             HUD_X + 70 + pad_x,
             HUD_Y + 20 + pad_y * 2,
             10,
@@ -451,38 +647,90 @@ impl Play {
         );
 
         r.draw_text(
-            "BOOST",
+            "SPEED",
             HUD_X + pad_x,
             HUD_Y + 30 + pad_y * 3,
             10,
             DEBUG_COLOR,
         );
         r.draw_text(
-            &format!("{} ", data.boost_active),
+            &format!("{:.2}", data.speed),
             HUD_X + 70 + pad_x,
             HUD_Y + 30 + pad_y * 3,
             10,
             DEBUG_COLOR,
         );
+
+        r.draw_text(
+            "BOOST",
+            HUD_X + pad_x,
+            HUD_Y + 40 + pad_y * 4,
+            10,
+            DEBUG_COLOR,
+        );
+        r.draw_text(
+            &format!("{} ", data.boost_active),
+            HUD_X + 70 + pad_x,
+            HUD_Y + 40 + pad_y * 4,
+            10,
+            DEBUG_COLOR,
+        );
         r.draw_text(
             &format!("{} ", data.boost_cooldown),
             HUD_X + 70 + pad_x,
-            HUD_Y + 30 + pad_y * 3,
+            HUD_Y + 40 + pad_y * 4,
             10,
             DEBUG_COLOR,
         );
 
+        for (i, (name, cooldown, ammo, heat)) in [data.primary, data.secondary].iter().enumerate() {
+            let y = HUD_Y + 50 + i as i32 * 10 + pad_y * (5 + i as i32);
+
+            r.draw_text(name, HUD_X + pad_x, y, 10, DEBUG_COLOR);
+            r.draw_text(
+                &format!("{}", cooldown),
+                HUD_X + 70 + pad_x,
+                y,
+                10,
+                DEBUG_COLOR,
+            );
+            r.draw_text(
+                &match ammo {
+                    Some(ammo) => ammo.to_string(),
+                    None => "-".to_string(),
+                },
+                HUD_X + 100 + pad_x,
+                y,
+                10,
+                DEBUG_COLOR,
+            );
+            r.draw_text(
+                &format!("{}", heat),
+                HUD_X + 130 + pad_x,
+                y,
+                10,
+                DEBUG_COLOR,
+            );
+        }
+
         r.draw_text(
-            "TORPEDO",
+            "FLARES",
             HUD_X + pad_x,
-            HUD_Y + 40 + pad_y * 4,
+            HUD_Y + 70 + pad_y * 7,
             10,
             DEBUG_COLOR,
         );
         r.draw_text(
-            &format!("{}", data.torpedo_cooldown),
+            &format!("{}", data.flares_cooldown),
             HUD_X + 70 + pad_x,
-            HUD_Y + 40 + pad_y * 4,
+            HUD_Y + 70 + pad_y * 7,
+            10,
+            DEBUG_COLOR,
+        );
+        r.draw_text(
+            &format!("{}", data.flares),
+            HUD_X + 100 + pad_x,
+            HUD_Y + 70 + pad_y * 7,
             10,
             DEBUG_COLOR,
         );
@@ -490,14 +738,14 @@ impl Play {
         r.draw_text(
             "TARGET",
             HUD_X + pad_x,
-            HUD_Y + 50 + pad_y * 5,
+            HUD_Y + 80 + pad_y * 8,
             10,
             DEBUG_COLOR,
         );
         r.draw_text(
             &format!("{}", data.target_timer),
             HUD_X + 70 + pad_x,
-            HUD_Y + 50 + pad_y * 5,
+            HUD_Y + 80 + pad_y * 8,
             10,
             DEBUG_COLOR,
         );
@@ -507,7 +755,7 @@ impl Play {
                 None => "-".to_string(),
             },
             HUD_X + pad_x,
-            HUD_Y + 60 + pad_y * 6,
+            HUD_Y + 90 + pad_y * 9,
             10,
             DEBUG_COLOR,
         );
@@ -516,7 +764,68 @@ impl Play {
             r.draw_text(
                 "LOCKED",
                 HUD_X + pad_x + 70,
-                HUD_Y + 60 + pad_y * 6,
+                HUD_Y + 90 + pad_y * 9,
+                10,
+                DEBUG_COLOR,
+            );
+        }
+
+        r.draw_text(
+            "DAMPENERS",
+            HUD_X + pad_x,
+            HUD_Y + 100 + pad_y * 10,
+            10,
+            DEBUG_COLOR,
+        );
+        r.draw_text(
+            if data.dampeners { "ON" } else { "OFF" },
+            HUD_X + 70 + pad_x,
+            HUD_Y + 100 + pad_y * 10,
+            10,
+            DEBUG_COLOR,
+        );
+
+        for (i, (team, score, state)) in data.objectives.iter().enumerate() {
+            let y = HUD_Y + 110 + i as i32 * 10 + pad_y * (11 + i as i32);
+
+            r.draw_text(
+                &format!("TEAM {}", team),
+                HUD_X + pad_x,
+                y,
+                10,
+                TEAM_COLORS[*team as usize % TEAM_COLORS.len()],
+            );
+            r.draw_text(
+                &format!("{}", score),
+                HUD_X + 70 + pad_x,
+                y,
+                10,
+                DEBUG_COLOR,
+            );
+            r.draw_text(
+                match state {
+                    FlagState::Home => "HOME",
+                    FlagState::Taken => "TAKEN",
+                    FlagState::Dropped => "DROPPED",
+                },
+                HUD_X + 100 + pad_x,
+                y,
+                10,
+                DEBUG_COLOR,
+            );
+        }
+
+        // active effects are listed below the objectives
+        let y_effects = HUD_Y + 110 + data.objectives.len() as i32 * (10 + pad_y) + 10;
+
+        for (i, (name, lifetime)) in data.effects.iter().enumerate() {
+            let y = y_effects + i as i32 * (10 + pad_y);
+
+            r.draw_text(name, HUD_X + pad_x, y, 10, DEBUG_COLOR);
+            r.draw_text(
+                &format!("{}", lifetime),
+                HUD_X + 100 + pad_x,
+                y,
                 10,
                 DEBUG_COLOR,
             );
@@ -565,14 +874,16 @@ impl Play {
         let x_top_left = ax.max(0.0);
         let y_top_left = ay.max(0.0);
 
-        let x_top_right = axw.min(COSMOS_WIDTH as f32);
+        let map = &self.maps.maps[self.map];
+
+        let x_top_right = axw.min(map.width as f32);
         let y_top_right = ay.max(0.0);
 
         let x_bottom_left = ax.max(0.0);
-        let y_bottom_left = ayh.min(COSMOS_HEIGHT as f32);
+        let y_bottom_left = ayh.min(map.height as f32);
 
-        let x_bottom_right = axw.min(COSMOS_WIDTH as f32);
-        let y_bottom_right = ayh.min(COSMOS_HEIGHT as f32);
+        let x_bottom_right = axw.min(map.width as f32);
+        let y_bottom_right = ayh.min(map.height as f32);
 
         let minimap = self.player_data.hud_data.minimap_xy;
 
@@ -609,6 +920,21 @@ impl Play {
         }
     }
 
+    fn receive_commands(&mut self, cid: u32, tick: u32, cmds: Box<[Command]>) {
+        // TODO: this might panic, investigate, make sure the index exists before we access it?
+        let tick_commands = &mut self.commands[tick as usize];
+
+        // add the client's commands
+        tick_commands.commands.push(EntityCommands {
+            id: self.player_data.map[&cid],
+            commands: cmds,
+        });
+
+        // if there are as many entity commands as there are clients,
+        // then we have received everything and are ready to progress
+        tick_commands.ready = tick_commands.commands.len() == self.network_data.client_ids.len();
+    }
+
     fn action(&mut self, bus: &mut Bus, h: &mut RaylibHandle) {
         while let Some(action) = self.actions.pop_last() {
             match action {
@@ -621,22 +947,117 @@ impl Play {
                 Action::ToggleDebug => {
                     bus.send(EngineRequestMessage::ToggleDebug);
                 }
-                Action::Synchronize(seed, cid, cids) => {
+                Action::Loopback(practice) => {
+                    // the same things the host would do, we're the only client there is
+                    let seed = h.get_random_value::<i32>(0..i32::MAX) as u32;
+                    h.set_random_seed(seed);
+
+                    let Some(map) = self.maps.maps.get(practice.map) else {
+                        panic!("wtf practice map {}", practice.map);
+                    };
+
+                    let settings = Settings {
+                        bots: practice.bots,
+                        map: map.name.clone(),
+                        map_hash: map.hash,
+                        ..Settings::new()
+                    };
+
+                    self.actions
+                        .insert(Action::Synchronize(seed, 0, settings, vec![0]));
+                }
+                Action::Synchronize(seed, cid, settings, cids) => {
+                    // the lobby made sure everyone has the same map
+                    // names alone can clash between two files, the hash can't
+                    let map = self
+                        .maps
+                        .maps
+                        .iter()
+                        .position(|x| x.name == settings.map && x.hash == settings.map_hash);
+
+                    self.map = match map {
+                        Some(map) => map,
+                        None => panic!("wtf map {}", settings.map),
+                    };
+
+                    let map = &self.maps.maps[self.map];
+
+                    self.quadtree = QuadTree::new(map.width, map.height);
+
                     // seed the stars
-                    for star in self.forge.stars(h) {
+                    for star in self.forge.stars(map, h) {
                         self.entities.add(Entity::Star(star));
                     }
 
+                    // build the obstacles
+                    for vertexes in map.obstacles.iter() {
+                        let obstacle = self.forge.obstacle(vertexes);
+                        self.entities.add(Entity::Obstacle(obstacle));
+                    }
+
+                    // seed the asteroid field
+                    for asteroid in self.forge.asteroids(map, h) {
+                        self.entities.add(Entity::Asteroid(asteroid));
+                    }
+
+                    // place the gravity wells
+                    for well in self.forge.wells(map, h) {
+                        self.entities.add(Entity::Well(well));
+                    }
+
+                    // scatter the pickups
+                    for pickup in self.forge.pickups(map, h) {
+                        self.entities.add(Entity::Pickup(pickup));
+                    }
+
+                    // place the objectives, one base and flag per team
+                    if settings.mode == Mode::CaptureTheFlag {
+                        for (team, (x, y)) in map.bases.iter().enumerate() {
+                            let position = Vector2::new(*x, *y);
+                            let team = team as u8;
+
+                            let base = self.forge.base(position, team);
+                            self.entities.add(Entity::Base(base));
+
+                            let flag = self.forge.flag(position, team);
+                            self.entities.add(Entity::Flag(flag));
+                        }
+                    }
+
+                    // bots join after the clients and get the ids from the top down
+                    let bots = (0..settings.bots as u32).map(|x| BOT_CLIENT_ID - x);
+
                     // create the players in the cosmos and set the player data
-                    for client_id in cids.iter() {
-                        let entity = Entity::Triship(self.forge.triship(Vector2::new(25.0, 25.0)));
+                    for (i, client_id) in cids.iter().copied().chain(bots).enumerate() {
+                        // in capture the flag players are split into two teams,
+                        // otherwise everyone is on their own
+                        let team = match settings.mode {
+                            Mode::Deathmatch => i as u8,
+                            Mode::CaptureTheFlag => (i % map.bases.len()) as u8,
+                        };
+
+                        let position = spawn_position(map, &settings, team);
+                        let class = settings.class(client_id);
+                        let entity = Entity::Triship(self.forge.triship(
+                            position,
+                            team,
+                            class,
+                            settings.physics,
+                        ));
                         let eid = self.entities.add(entity);
 
                         self.player_data.entity_ids.push(eid);
-                        self.player_data.map.insert(*client_id, eid);
+                        self.player_data.map.insert(client_id, eid);
+                        self.player_data.teams.insert(client_id, team);
+
+                        if client_id == cid {
+                            self.player_data.player_entity_id = Some(eid);
+                        }
 
-                        if *client_id == cid {
-                            self.player_data.player_entity_id = eid;
+                        if i >= cids.len() {
+                            self.player_data
+                                .bots
+                                .insert(client_id, Bot::new(settings.difficulty));
                         }
                     }
 
@@ -651,14 +1072,24 @@ impl Play {
 
                     // set the networking data
                     self.network_data.seed = seed;
+                    self.network_data.settings = settings;
                     self.network_data.client_id = cid;
                     self.network_data.client_ids = cids;
 
                     // we are now fully synced and can begin playing!
                     self.synchronized = true;
                 }
-                Action::TogglePause => {
-                    bus.send(NetRequestMessage::TogglePause);
+                Action::TogglePause => match self.source {
+                    Source::Network => bus.send(NetRequestMessage::TogglePause),
+                    Source::Loopback(_) => self.paused = !self.paused,
+                },
+                Action::Leave => {
+                    let state = match self.source {
+                        Source::Loopback(Practice { editor: true, .. }) => State::Editor,
+                        _ => State::Menu,
+                    };
+
+                    bus.send(StateRequestMessage::Set(state));
                 }
             }
         }
@@ -672,11 +1103,28 @@ impl Play {
                 return true;
             }
 
-            let entity = self.forge.triship(Vector2::new(25.0, 25.0));
+            let cid = self
+                .player_data
+                .map
+                .iter()
+                .find(|(_, entity_id)| *entity_id == eid)
+                .map(|(cid, _)| *cid);
+
+            let team = cid
+                .and_then(|cid| self.player_data.teams.get(&cid))
+                .copied()
+                .unwrap_or(0);
+
+            let settings = &self.network_data.settings;
+            let class = cid.map(|cid| settings.class(cid)).unwrap_or(0);
+
+            let map = &self.maps.maps[self.map];
+            let position = spawn_position(map, settings, team);
+            let entity = self.forge.triship(position, team, class, settings.physics);
             let new_eid = self.entities.add(Entity::Triship(entity));
 
-            if self.player_data.player_entity_id == *eid {
-                self.player_data.player_entity_id = new_eid;
+            if self.player_data.player_entity_id == Some(*eid) {
+                self.player_data.player_entity_id = Some(new_eid);
             }
 
             for (_, entity_id) in self.player_data.map.iter_mut() {
@@ -693,13 +1141,29 @@ impl Play {
     }
 
     fn update_player_data(&mut self) {
-        let Some(eidx) = self.entities.entity(self.player_data.player_entity_id) else {
+        // objectives are shown even while the player is waiting to respawn
+        self.player_data.hud_data.objectives = self
+            .entities
+            .bases
+            .iter()
+            .map(|base| {
+                let team = base.entity.team;
+                let state = match self.entities.flags.iter().find(|x| x.entity.team == team) {
+                    Some(flag) if flag.entity.carrier.is_some() => FlagState::Taken,
+                    Some(flag) if !flag_home(&flag.entity) => FlagState::Dropped,
+                    _ => FlagState::Home,
+                };
+
+                (team, base.entity.score, state)
+            })
+            .collect();
+
+        let Some(player_id) = self.player_data.player_entity_id else {
             return;
         };
 
-        let e = match eidx {
-            EntityIndex::Triship(idx) => &self.entities.triships[idx].entity,
-            _ => return,
+        let Some(e) = self.entities.triship(player_id) else {
+            return;
         };
 
         // the player centroid is used to set the camera target,
@@ -711,6 +1175,7 @@ impl Play {
         let hud = &mut self.player_data.hud_data;
 
         hud.life = e.life;
+        hud.shield = e.shield.current;
         hud.speed = e.motion.velocity.length();
         hud.boost_active = if e.boost.active {
             e.boost.lifetime.current
@@ -724,7 +1189,22 @@ impl Play {
             0
         };
 
-        hud.torpedo_cooldown = e.cooldown_torpedo.current;
+        hud.dampeners = e.dampeners;
+        hud.primary = weapon_hud(&e.primary);
+        hud.secondary = weapon_hud(&e.secondary);
+        hud.flares = e.countermeasures.ammo.current;
+        hud.flares_cooldown = e.countermeasures.cooldown.current;
+        hud.effects = e
+            .effects
+            .iter()
+            .map(|x| {
+                let name = match x.kind {
+                    EffectKind::RapidFire => "RAPID FIRE",
+                };
+
+                (name, x.lifetime.current)
+            })
+            .collect();
 
         hud.target = e.targeting.eid;
 
@@ -734,18 +1214,54 @@ impl Play {
             hud.target_timer = 0;
         }
 
+        // warn the player about anyone locking on to them and torpedoes headed their way
+        hud.lock = LockState::None;
+        hud.incoming = 0;
+        hud.threats.clear();
+
+        for x in self.entities.triships.iter() {
+            let t = &x.entity.targeting;
+            if t.eid != Some(player_id) || t.timer.current == t.timer.max {
+                continue;
+            }
+
+            let locked = t.timer.current == 0;
+
+            // a finished lock outranks one still in progress
+            if locked {
+                hud.lock = LockState::Locked;
+            } else if let LockState::None = hud.lock {
+                hud.lock = LockState::Locking;
+            }
+
+            let color = if locked { Color::RED } else { Color::ORANGE };
+
+            hud.threats.push((x.entity.body.polygon.bounds_real, color));
+        }
+
+        for x in self.entities.torpedoes.iter() {
+            if x.entity.target != Some(player_id) {
+                continue;
+            }
+
+            hud.incoming += 1;
+
+            hud.threats
+                .push((x.entity.body.polygon.bounds_real, Color::RED));
+        }
+
         hud.minimap_xy = self.camera_target.new;
-        hud.minimap_entities = self
-            .quadtree
-            .get(
-                &Rectangle {
-                    x: hud.minimap_xy.x - (MINIMAP_AREA_WIDTH / 2) as f32,
-                    y: hud.minimap_xy.y - (MINIMAP_AREA_HEIGHT / 2) as f32,
-                    width: MINIMAP_AREA_WIDTH as f32,
-                    height: MINIMAP_AREA_HEIGHT as f32,
-                },
-                &self.entities,
-            )
+        self.quadtree.get(
+            &Rectangle {
+                x: hud.minimap_xy.x - (MINIMAP_AREA_WIDTH / 2) as f32,
+                y: hud.minimap_xy.y - (MINIMAP_AREA_HEIGHT / 2) as f32,
+                width: MINIMAP_AREA_WIDTH as f32,
+                height: MINIMAP_AREA_HEIGHT as f32,
+            },
+            &mut hud.minimap_found,
+        );
+        hud.minimap_entities = hud
+            .minimap_found
             .iter()
             .filter_map(|eidx_rnd| match eidx_rnd {
                 EntityIndex::Triship(idx) => Some((
@@ -757,7 +1273,7 @@ impl Play {
                         .shape
                         .centroid(),
                     4.0,
-                    if *eidx_rnd == eidx {
+                    if self.entities.triships[*idx].id == player_id {
                         Color::WHITESMOKE
                     } else {
                         Color::RED
@@ -774,6 +1290,42 @@ impl Play {
                     2.0,
                     Color::RED,
                 )),
+                EntityIndex::Flag(idx) => {
+                    let e = &self.entities.flags[*idx].entity;
+                    Some((e.body.state.new.shape.centroid(), 3.0, e.body.color))
+                }
+                EntityIndex::Base(idx) => {
+                    let e = &self.entities.bases[*idx].entity;
+                    Some((e.body.state.new.shape.centroid(), 6.0, e.body.color))
+                }
+                EntityIndex::Pickup(idx) => {
+                    let e = &self.entities.pickups[*idx].entity;
+                    Some((e.body.state.new.shape.centroid(), 2.0, e.body.color))
+                }
+                EntityIndex::Well(idx) => {
+                    let e = &self.entities.wells[*idx].entity;
+                    Some((
+                        e.body.state.new.shape.center,
+                        (e.radius / 40.0).max(2.0),
+                        e.body.color,
+                    ))
+                }
+                EntityIndex::Obstacle(idx) => {
+                    let b = self.entities.obstacles[*idx].entity.polygon.bounds_real.new;
+                    Some((
+                        b.centroid(),
+                        (b.width.max(b.height) / 40.0).max(2.0),
+                        Color::LIGHTGRAY,
+                    ))
+                }
+                EntityIndex::Asteroid(idx) => {
+                    let e = &self.entities.asteroids[*idx].entity;
+                    Some((
+                        e.body.state.new.shape.centroid(),
+                        (e.radius / 40.0).max(1.0),
+                        Color::GRAY,
+                    ))
+                }
                 _ => None,
             })
             .map(|(centroid, size, color)| {
@@ -793,13 +1345,7 @@ impl Play {
         r.target = p.hud_data.target;
         r.target_timer = p.hud_data.target_timer;
 
-        r.target_eidx = match r.target {
-            Some(eid) => self.entities.entity(eid),
-            None => None,
-        };
-
         r.player_entity_id = p.player_entity_id;
-        r.player_eidx = self.entities.entity(r.player_entity_id);
     }
 
     fn reset_data(&mut self) {
@@ -808,9 +1354,7 @@ impl Play {
 
         r.target = None;
         r.target_timer = 0;
-        r.target_eidx = None;
-        r.player_entity_id = 0;
-        r.player_eidx = None;
+        r.player_entity_id = None;
 
         // set the camera target to the latest known position
         self.camera_target = Generation {
@@ -819,8 +1363,42 @@ impl Play {
         };
 
         p.hud_data.life = 0.0;
+        p.hud_data.shield = 0.0;
         p.hud_data.speed = 0.0;
         p.hud_data.boost_cooldown = 0;
-        p.hud_data.torpedo_cooldown = 0;
+        p.hud_data.dampeners = false;
+        p.hud_data.primary = ("-", 0, None, 0);
+        p.hud_data.secondary = ("-", 0, None, 0);
+        p.hud_data.flares = 0;
+        p.hud_data.flares_cooldown = 0;
+        p.hud_data.effects.clear();
+        p.hud_data.lock = LockState::None;
+        p.hud_data.incoming = 0;
+        p.hud_data.threats.clear();
     }
 }
+
+fn weapon_hud(slot: &Slot) -> (&'static str, u8, Option<u8>, u8) {
+    match slot.weapons.get(slot.selected) {
+        Some(w) => (
+            if w.heat.overheated {
+                "OVERHEATED"
+            } else {
+                w.kind.name()
+            },
+            w.cooldown.current,
+            w.ammo.as_ref().map(|x| x.current),
+            w.heat.current,
+        ),
+        None => ("-", 0, None, 0),
+    }
+}
+
+fn spawn_position(map: &Map, settings: &Settings, team: u8) -> Vector2 {
+    let (x, y) = match settings.mode {
+        Mode::Deathmatch => map.spawns[team as usize % map.spawns.len()],
+        Mode::CaptureTheFlag => map.bases[team as usize % map.bases.len()],
+    };
+
+    Vector2::new(x, y)
+}
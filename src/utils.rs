@@ -40,3 +40,15 @@ pub fn minimap_translate(x: f32, y: f32, minimap: Vector2) -> Vector2 {
 
     Vector2::new(x, y)
 }
+
+pub fn hash(bytes: &[u8]) -> u32 {
+    // fnv-1a, we need something that hashes the same everywhere
+    let mut hash: u32 = 0x811c9dc5;
+
+    for byte in bytes {
+        hash ^= *byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+
+    hash
+}